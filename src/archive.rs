@@ -0,0 +1,244 @@
+use std::path::Path;
+
+use chrono::NaiveTime;
+use serde::Serialize;
+
+use crate::{
+    i18n::Message,
+    timetable::{self, models::Day},
+    utils::{fetcher::Fetcher, file, get_school_year},
+};
+
+/// Levels published on the website
+const LEVELS: [i8; 2] = [1, 2];
+
+/// Semesters of a year
+const SEMESTERS: [i8; 2] = [1, 2];
+
+/// Parsed timetable, as stored in the archive
+#[derive(Serialize)]
+struct Page<'a> {
+    level: i8,
+    semester: i8,
+    year: &'a str,
+    schedules: &'a [NaiveTime],
+    days: &'a [Day],
+}
+
+/// Summary of an archived timetable
+#[derive(Serialize)]
+struct Entry {
+    level: i8,
+    semester: i8,
+    /// Raw webpage
+    html: Option<String>,
+    /// Parsed timetable
+    json: Option<String>,
+    /// Number of courses found
+    courses: usize,
+    /// Outdated copy from the cache, the website couldn't be reached
+    stale: bool,
+    /// Why the timetable couldn't be archived
+    error: Option<String>,
+}
+
+/// Content of the archive
+#[derive(Serialize)]
+struct Manifest {
+    generator: String,
+    year: String,
+    date: String,
+    entries: Vec<Entry>,
+}
+
+/// Archive every timetable of the year in a dated directory
+pub async fn archive(fetcher: &Fetcher, year_opt: Option<i32>, output: &Path) {
+    // Same school year for both semesters
+    let year = get_school_year(year_opt);
+    let date = chrono::Local::now().format("%Y-%m-%d").to_string();
+
+    let directory = output.join(&year).join(&date);
+    crate::check_written(
+        std::fs::create_dir_all(&directory),
+        &directory.display().to_string(),
+    );
+
+    let mut entries = vec![];
+    for level in LEVELS {
+        for semester in SEMESTERS {
+            eprintln!("{}", Message::Archiving(level, semester));
+
+            let mut entry = Entry {
+                level,
+                semester,
+                html: None,
+                json: None,
+                courses: 0,
+                stale: false,
+                error: None,
+            };
+            // Carry on with the other timetables, the failure is in the manifest
+            if let Err(err) = store(&mut entry, fetcher, &directory, &year).await {
+                eprintln!("{}", Message::Skipped(&err));
+                entry.error = Some(err);
+            }
+            entries.push(entry);
+        }
+    }
+
+    let manifest = Manifest {
+        generator: format!("cal7tor/{}", env!("CARGO_PKG_VERSION")),
+        year,
+        date,
+        entries,
+    };
+    let path = directory.join("manifest.json");
+    crate::check_written(
+        file::write_atomic(&path, &serde_json::to_string_pretty(&manifest).unwrap()),
+        &path.display().to_string(),
+    );
+
+    eprintln!("{}", Message::ArchiveBuilt(&directory.display()));
+}
+
+/// Store the webpage and the parsed timetable of a class, filling its entry
+async fn store(
+    entry: &mut Entry,
+    fetcher: &Fetcher,
+    directory: &Path,
+    year: &str,
+) -> Result<(), String> {
+    let (level, semester) = (entry.level, entry.semester);
+
+    // The archive keeps the current state of the website
+    let page = fetcher
+        .get_page(level, semester, year, true)
+        .await
+        .map_err(|err| err.to_string())?;
+    entry.stale = page.stale;
+    let html = page.html;
+
+    let html_name = Fetcher::page_name(level, semester);
+    write(directory, &html_name, &html)?;
+    entry.html = Some(html_name);
+
+    let timetable = timetable::parse(&scraper::Html::parse_document(&html), level, semester)?;
+    let page = Page {
+        level,
+        semester,
+        year,
+        schedules: &timetable.0,
+        days: &timetable.1 .1,
+    };
+    let json_name = format!("M{level}-S{semester}.json");
+    write(
+        directory,
+        &json_name,
+        &serde_json::to_string_pretty(&page).unwrap(),
+    )?;
+    entry.json = Some(json_name);
+
+    entry.courses = timetable
+        .1
+         .1
+        .iter()
+        .map(|day| day.courses.iter().flatten().count())
+        .sum();
+
+    Ok(())
+}
+
+/// Write a file of the archive
+fn write(directory: &Path, name: &str, content: &str) -> Result<(), String> {
+    file::write_atomic(&directory.join(name), content)
+        .map_err(|err| Message::WriteFailed(name, &err).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{Arc, Mutex},
+        time::Duration,
+    };
+
+    use serde_json::{json, Value};
+
+    use super::archive;
+    use crate::utils::{
+        fetcher::Fetcher,
+        file::temp_dir,
+        server::{serve, Response},
+    };
+
+    const PAGE: &str = "<html><body><table><tbody><tr>\
+        <td title=\"COURS Algo M1 : lundi 8h30 (durée : 2h00)\" rowspan=\"8\">\
+        <b>x<br>Amphi<br></b></td></tr></tbody></table></body></html>";
+
+    #[tokio::test]
+    async fn manifest() {
+        let down = Arc::new(Mutex::new(false));
+        let failing = Arc::clone(&down);
+        let url = serve(
+            move |request| match request.path.rsplit('=').next().unwrap() {
+                _ if *failing.lock().unwrap() => Response::new("503 Service Unavailable", ""),
+                "M1,1" => Response::new("200 OK", PAGE),
+                "M1,2" => Response::new("200 OK", "Aucun créneau horaire affecté"),
+                _ => Response::new("404 Not Found", ""),
+            },
+        );
+        let output = temp_dir("archive");
+        let fetcher = Fetcher::new(
+            "test",
+            Duration::from_secs(5),
+            None,
+            Some(&url),
+            0,
+            false,
+            None,
+        )
+        .unwrap()
+        .with_cache(Some(output.join("cache")));
+
+        let manifest = || {
+            let year = output.join("2026-2027");
+            let directory = std::fs::read_dir(&year).unwrap().next().unwrap().unwrap();
+            let content = std::fs::read_to_string(directory.path().join("manifest.json")).unwrap();
+            serde_json::from_str::<Value>(&content).unwrap()
+        };
+
+        archive(&fetcher, Some(2026), &output).await;
+        let first = manifest();
+        assert_eq!(first["year"], "2026-2027");
+        assert_eq!(
+            first["entries"][0],
+            json!({
+                "level": 1,
+                "semester": 1,
+                "html": "M1-S1.html",
+                "json": "M1-S1.json",
+                "courses": 1,
+                "stale": false,
+                "error": null,
+            })
+        );
+        for entry in first["entries"].as_array().unwrap().iter().skip(1) {
+            assert_eq!(entry["html"], Value::Null);
+            assert_eq!(entry["courses"], 0);
+            assert!(entry["error"].is_string());
+        }
+        assert!(first["entries"][1]["error"]
+            .as_str()
+            .unwrap()
+            .contains("Aucun créneau horaire affecté"));
+
+        // The website is down, the cached copy is archived and flagged
+        *down.lock().unwrap() = true;
+        archive(&fetcher, Some(2026), &output).await;
+        let second = manifest();
+        assert_eq!(second["entries"][0]["stale"], true);
+        assert_eq!(second["entries"][0]["courses"], 1);
+        assert!(second["entries"][3]["error"].is_string());
+
+        let _ = std::fs::remove_dir_all(output);
+    }
+}
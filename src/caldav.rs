@@ -0,0 +1,523 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Write,
+    path::Path,
+};
+
+use regex::Regex;
+use reqwest::{
+    header::{CONTENT_TYPE, ETAG, IF_MATCH},
+    Method, RequestBuilder, StatusCode,
+};
+
+use crate::utils::{cache, fetcher::Fetcher};
+
+/// Prefix of the resources managed by cal7tor, the others are left untouched
+const PREFIX: &str = "cal7tor-";
+
+/// Ask for the resources of the collection
+const PROPFIND: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<d:propfind xmlns:d="DAV:"><d:prop><d:getetag/></d:prop></d:propfind>"#;
+
+/// Credentials of the `CalDAV` server, when not given in the URL
+pub struct Credentials {
+    pub user: String,
+    pub password: Option<String>,
+}
+
+/// What changed in the collection
+#[derive(Default)]
+pub struct Report {
+    pub created: usize,
+    pub updated: usize,
+    pub unchanged: usize,
+    pub deleted: usize,
+}
+
+/// Resource uploaded by a previous run
+struct Uploaded {
+    /// Hash of the content sent
+    hash: String,
+    /// `ETag` given by the server to the content
+    etag: Option<String>,
+}
+
+/// Resources uploaded to a collection, to skip the unchanged ones
+#[derive(Default)]
+pub struct State {
+    resources: HashMap<String, Uploaded>,
+}
+
+impl State {
+    /// Name of the state of the collection in the cache
+    fn cache_name(collection: &str) -> String {
+        format!(
+            "caldav-{}.state",
+            redact(collection).replace(|c: char| !c.is_ascii_alphanumeric(), "_")
+        )
+    }
+
+    /// Load the state of the collection from the cache
+    pub fn load(cache: Option<&Path>, collection: &str) -> Self {
+        let resources = cache
+            .and_then(|dir| cache::read(dir, &Self::cache_name(collection)))
+            .unwrap_or_default()
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.split('\t');
+                let name = fields.next()?.to_owned();
+                let hash = fields.next()?.to_owned();
+                let etag = fields.next().map(str::to_owned);
+                Some((name, Uploaded { hash, etag }))
+            })
+            .collect();
+
+        Self { resources }
+    }
+
+    /// Store the state of the collection in the cache
+    pub fn store(&self, cache: Option<&Path>, collection: &str) {
+        let Some(dir) = cache else {
+            return;
+        };
+
+        let mut content = String::new();
+        for (name, uploaded) in &self.resources {
+            let _ = write!(content, "{name}\t{}", uploaded.hash);
+            if let Some(etag) = &uploaded.etag {
+                let _ = write!(content, "\t{etag}");
+            }
+            content.push('\n');
+        }
+
+        cache::write(dir, &Self::cache_name(collection), &content);
+    }
+}
+
+/// Upload the resources to the collection, as `(UID, calendar)` pairs, and delete
+/// the resources previously uploaded which are not there anymore, the resources
+/// unchanged since the last upload recorded in the state are skipped
+pub async fn upload(
+    fetcher: &Fetcher,
+    collection: &str,
+    credentials: Option<&Credentials>,
+    resources: Vec<(String, String)>,
+    state: &mut State,
+) -> Result<Report, String> {
+    let collection = format!("{}/", collection.trim_end_matches('/'));
+    let request = |method: Method, url: &str| {
+        let mut request = fetcher.client().request(method, url);
+        if let Some(credentials) = credentials {
+            request = request.basic_auth(&credentials.user, credentials.password.as_ref());
+        }
+        request
+    };
+
+    let existing = list(fetcher, &collection, || {
+        request(Method::from_bytes(b"PROPFIND").unwrap(), &collection)
+    })
+    .await?;
+
+    let mut report = Report::default();
+    let mut uploaded = HashSet::new();
+    for (uid, calendar) in resources {
+        let name = format!("{PREFIX}{uid}.ics");
+        let url = format!("{collection}{name}");
+        let hash = hash(&calendar);
+        let etag = existing.get(&name);
+
+        // Still the content uploaded last time
+        let unchanged = state.resources.get(&name).is_some_and(|previous| {
+            previous.hash == hash && previous.etag.is_some() && etag == Some(&previous.etag)
+        });
+        if unchanged {
+            report.unchanged += 1;
+            uploaded.insert(name);
+            continue;
+        }
+
+        let response = fetcher
+            .send(|| {
+                let mut request = request(Method::PUT, &url)
+                    .header(CONTENT_TYPE, "text/calendar; charset=utf-8")
+                    .body(calendar.clone());
+                // Don't overwrite a change made since the listing
+                if let Some(Some(etag)) = etag {
+                    request = request.header(IF_MATCH, etag);
+                }
+                request
+            })
+            .await
+            .map_err(|err| err.to_string())?;
+        if !response.status().is_success() {
+            return Err(format!("{} : {}", redact(&url), response.status()));
+        }
+
+        if etag.is_some() {
+            report.updated += 1;
+        } else {
+            report.created += 1;
+        }
+        state.resources.insert(
+            name.clone(),
+            Uploaded {
+                hash,
+                etag: response
+                    .headers()
+                    .get(ETAG)
+                    .and_then(|value| value.to_str().ok())
+                    .map(str::to_owned),
+            },
+        );
+        uploaded.insert(name);
+    }
+
+    // Courses removed from the timetable
+    for name in existing.keys().filter(|name| !uploaded.contains(*name)) {
+        let url = format!("{collection}{name}");
+
+        let response = fetcher
+            .send(|| request(Method::DELETE, &url))
+            .await
+            .map_err(|err| err.to_string())?;
+        if !response.status().is_success() && response.status() != StatusCode::NOT_FOUND {
+            return Err(format!("{} : {}", redact(&url), response.status()));
+        }
+
+        state.resources.remove(name);
+        report.deleted += 1;
+    }
+
+    Ok(report)
+}
+
+/// Hash of the calendar, without its timestamps which change on every export
+fn hash(calendar: &str) -> String {
+    let content = calendar
+        .lines()
+        .filter(|line| !line.starts_with("DTSTAMP"))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    uuid::Uuid::new_v5(&uuid::Uuid::NAMESPACE_OID, content.as_bytes()).to_string()
+}
+
+/// Resources of the collection uploaded by cal7tor, with their `ETag`
+async fn list(
+    fetcher: &Fetcher,
+    collection: &str,
+    request: impl Fn() -> RequestBuilder,
+) -> Result<HashMap<String, Option<String>>, String> {
+    let response = fetcher
+        .send(|| {
+            request()
+                .header("Depth", "1")
+                .header(CONTENT_TYPE, "application/xml; charset=utf-8")
+                .body(PROPFIND)
+        })
+        .await
+        .map_err(|err| err.to_string())?;
+    if response.status() != StatusCode::MULTI_STATUS {
+        return Err(format!("{} : {}", redact(collection), response.status()));
+    }
+    let body = response.text().await.map_err(|err| err.to_string())?;
+
+    // The namespace prefix depends on the server
+    let element = |name: &str| {
+        Regex::new(&format!(
+            r"(?s)<(?:[\w-]+:)?{name}\b[^>]*>(.*?)</(?:[\w-]+:)?{name}>"
+        ))
+        .unwrap()
+    };
+    let (re_response, re_href, re_etag) =
+        (element("response"), element("href"), element("getetag"));
+
+    Ok(re_response
+        .captures_iter(&body)
+        .filter_map(|response| {
+            let name = re_href.captures(&response[1])?[1]
+                .trim_end_matches('/')
+                .rsplit('/')
+                .next()?
+                .to_owned();
+            let etag = re_etag
+                .captures(&response[1])
+                .map(|etag| etag[1].trim().replace("&quot;", "\"").replace("&amp;", "&"));
+            Some((name, etag))
+        })
+        .filter(|(name, _)| {
+            name.starts_with(PREFIX)
+                && std::path::Path::new(name)
+                    .extension()
+                    .is_some_and(|ext| ext.eq_ignore_ascii_case("ics"))
+        })
+        .collect())
+}
+
+/// URL without its password, to show it in errors
+fn redact(url: &str) -> String {
+    reqwest::Url::parse(url).map_or_else(
+        |_| url.to_owned(),
+        |mut url| {
+            let _ = url.set_password(None);
+            url.to_string()
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::HashMap,
+        fmt::Write as _,
+        sync::{Arc, Mutex},
+        time::Duration,
+    };
+
+    use chrono::{TimeZone, Utc};
+
+    use super::{upload, State};
+    use crate::{
+        ics,
+        timetable::models::{self, Category, Course},
+        utils::{
+            fetcher::Fetcher,
+            server::{serve, Response},
+        },
+    };
+
+    /// Resources of the collection, with their `ETag`
+    type Collection = Arc<Mutex<HashMap<String, (String, String)>>>;
+
+    /// Serve a collection holding the given resources, logging the requests
+    /// as `METHOD name`, returns its URL
+    fn server(collection: Collection, log: Arc<Mutex<Vec<String>>>) -> String {
+        let mut version = 0;
+        let url = serve(move |request| {
+            let name = request.path.rsplit('/').next().unwrap().to_owned();
+
+            let mut resources = collection.lock().unwrap();
+            let response = match request.method.as_str() {
+                "PROPFIND" => {
+                    let mut content = "<?xml version=\"1.0\"?>\
+                        <d:multistatus xmlns:d=\"DAV:\">\
+                        <d:response><d:href>/calendar/</d:href></d:response>"
+                        .to_owned();
+                    for (name, (etag, _)) in resources.iter() {
+                        write!(
+                            content,
+                            "<d:response><d:href>/calendar/{name}</d:href><d:propstat>\
+                            <d:prop><d:getetag>{}</d:getetag></d:prop>\
+                            </d:propstat></d:response>",
+                            etag.replace('"', "&quot;")
+                        )
+                        .unwrap();
+                    }
+                    content.push_str("</d:multistatus>");
+                    Response::new("207 Multi-Status", content)
+                }
+                "PUT" => {
+                    let current = resources.get(&name).map(|(etag, _)| etag);
+                    match request.headers.get("if-match") {
+                        Some(expected) if Some(expected) != current => {
+                            Response::new("412 Precondition Failed", "")
+                        }
+                        _ => {
+                            version += 1;
+                            let etag = format!("\"{version}\"");
+                            resources.insert(name.clone(), (etag.clone(), request.body.clone()));
+                            Response::new("201 Created", "").header("ETag", etag)
+                        }
+                    }
+                }
+                "DELETE" => {
+                    resources.remove(&name);
+                    Response::new("204 No Content", "")
+                }
+                _ => Response::new("405 Method Not Allowed", ""),
+            };
+            drop(resources);
+
+            let if_match = if request.headers.contains_key("if-match") {
+                " if-match"
+            } else {
+                ""
+            };
+            log.lock().unwrap().push(
+                format!("{} {name}{if_match}", request.method)
+                    .trim_end()
+                    .to_owned(),
+            );
+
+            response
+        });
+
+        format!("{url}/calendar")
+    }
+
+    fn calendar(summary: &str, stamp: &str) -> String {
+        format!(
+            "BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nDTSTAMP:{stamp}\r\nSUMMARY:{summary}\r\n\
+            END:VEVENT\r\nEND:VCALENDAR\r\n"
+        )
+    }
+
+    #[tokio::test]
+    async fn only_the_changes_are_sent() {
+        let collection = Collection::default();
+        // Not uploaded by cal7tor, left untouched
+        collection.lock().unwrap().insert(
+            "perso.ics".to_owned(),
+            ("\"perso\"".to_owned(), String::new()),
+        );
+        let log = Arc::default();
+        let url = server(Arc::clone(&collection), Arc::clone(&log));
+        let fetcher =
+            Fetcher::new("test", Duration::from_secs(5), None, None, 0, false, None).unwrap();
+        let mut state = State::default();
+
+        let report = upload(
+            &fetcher,
+            &url,
+            None,
+            vec![
+                ("a".to_owned(), calendar("A", "20260901T080000Z")),
+                ("b".to_owned(), calendar("B", "20260901T080000Z")),
+                ("c".to_owned(), calendar("C", "20260901T080000Z")),
+            ],
+            &mut state,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            (
+                report.created,
+                report.updated,
+                report.unchanged,
+                report.deleted
+            ),
+            (3, 0, 0, 0)
+        );
+
+        log.lock().unwrap().clear();
+        let report = upload(
+            &fetcher,
+            &url,
+            None,
+            vec![
+                // Only the timestamp changed
+                ("a".to_owned(), calendar("A", "20260908T080000Z")),
+                (
+                    "b".to_owned(),
+                    calendar("B, salle 2001", "20260908T080000Z"),
+                ),
+                ("d".to_owned(), calendar("D", "20260908T080000Z")),
+            ],
+            &mut state,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            (
+                report.created,
+                report.updated,
+                report.unchanged,
+                report.deleted
+            ),
+            (1, 1, 1, 1)
+        );
+
+        let mut requests = log.lock().unwrap().clone();
+        requests.sort();
+        assert_eq!(
+            requests,
+            [
+                "DELETE cal7tor-c.ics",
+                "PROPFIND",
+                "PUT cal7tor-b.ics if-match",
+                "PUT cal7tor-d.ics",
+            ]
+        );
+
+        let mut names = collection
+            .lock()
+            .unwrap()
+            .keys()
+            .cloned()
+            .collect::<Vec<_>>();
+        names.sort();
+        assert_eq!(
+            names,
+            [
+                "cal7tor-a.ics",
+                "cal7tor-b.ics",
+                "cal7tor-d.ics",
+                "perso.ics"
+            ]
+        );
+        assert!(collection.lock().unwrap()["cal7tor-b.ics"]
+            .1
+            .contains("salle 2001"));
+    }
+
+    #[tokio::test]
+    async fn changes_made_elsewhere_are_replaced() {
+        let collection = Collection::default();
+        let log = Arc::default();
+        let url = server(Arc::clone(&collection), Arc::clone(&log));
+        let fetcher =
+            Fetcher::new("test", Duration::from_secs(5), None, None, 0, false, None).unwrap();
+        let mut state = State::default();
+
+        let resources = vec![("a".to_owned(), calendar("A", "20260901T080000Z"))];
+        upload(&fetcher, &url, None, resources.clone(), &mut state)
+            .await
+            .unwrap();
+
+        // Edited by another client, the course is sent again
+        collection
+            .lock()
+            .unwrap()
+            .get_mut("cal7tor-a.ics")
+            .unwrap()
+            .0 = "\"edited\"".to_owned();
+        let report = upload(&fetcher, &url, None, resources, &mut state)
+            .await
+            .unwrap();
+        assert_eq!((report.updated, report.unchanged), (1, 0));
+    }
+
+    #[tokio::test]
+    async fn parallel_groups_are_both_sent() {
+        let collection = Collection::default();
+        let log = Arc::default();
+        let url = server(Arc::clone(&collection), Arc::clone(&log));
+        let fetcher =
+            Fetcher::new("test", Duration::from_secs(5), None, None, 0, false, None).unwrap();
+
+        let start = Utc.with_ymd_and_hms(2026, 9, 14, 8, 0, 0).unwrap();
+        let first = Course {
+            dtstart: Some(start),
+            dtend: Some(start + chrono::Duration::hours(2)),
+            data: Some("Groupe 1".to_owned()),
+            ..models::course("Algo", Category::TD, (8, 10))
+        };
+        let second = Course {
+            data: Some("Groupe 2".to_owned()),
+            ..first.clone()
+        };
+        let resources = ics::resources(vec![first, second], false, &ics::metadata());
+
+        upload(&fetcher, &url, None, resources, &mut State::default())
+            .await
+            .unwrap();
+        let puts = log
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|request| request.starts_with("PUT"))
+            .count();
+        assert_eq!(puts, 2);
+        assert_eq!(collection.lock().unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,437 @@
+use std::{collections::BTreeMap, ffi::OsString, path::PathBuf};
+
+use clap::{
+    builder::BoolishValueParser, parser::ValueSource, Arg, ArgAction, ArgMatches, CommandFactory,
+    FromArgMatches,
+};
+
+use crate::i18n::Message;
+
+/// Name of the project-local configuration file
+const LOCAL: &str = "cal7tor.toml";
+
+/// Name of the value of the flags
+const FLAG: &str = "BOOL";
+
+/// Prefix of the environment variables giving defaults
+const ENV_PREFIX: &str = "CAL7TOR_";
+
+/// Arguments with their origin, to show the effective configuration
+pub struct Effective {
+    /// Final arguments
+    matches: ArgMatches,
+    /// Where each argument comes from, when not from the default values
+    sources: BTreeMap<String, Source>,
+    /// Positional arguments of the configuration, unused by the subcommands
+    skipped: BTreeMap<String, Vec<toml::Value>>,
+    /// Every argument of the command
+    arguments: Vec<Arg>,
+}
+
+/// Origin of an argument
+#[derive(Clone)]
+enum Source {
+    CommandLine,
+    Environment,
+    File(String),
+}
+
+impl std::fmt::Display for Source {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::CommandLine => Message::CommandLine.fmt(f),
+            Self::Environment => Message::Environment.fmt(f),
+            Self::File(path) => f.write_str(path),
+        }
+    }
+}
+
+/// User configuration file, following the XDG specification
+fn user_file() -> Option<PathBuf> {
+    std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+        .map(|dir| dir.join("cal7tor").join("config.toml"))
+}
+
+/// Read a configuration file, missing files are ignored
+fn read(path: &PathBuf) -> Option<toml::Table> {
+    let content = std::fs::read_to_string(path).ok()?;
+
+    match content.parse::<toml::Table>() {
+        Ok(table) => Some(table),
+        Err(err) => {
+            eprintln!("{}", Message::InvalidConfig(&path.display(), &err));
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Parse the command line, the arguments not given there come from the
+/// environment, then from the project-local file, then from the user file
+pub fn parse<P: CommandFactory + FromArgMatches>() -> (P, Effective) {
+    let argv = std::env::args_os().collect::<Vec<_>>();
+
+    // Later files take precedence
+    let files = [user_file(), Some(PathBuf::from(LOCAL))]
+        .into_iter()
+        .flatten()
+        .filter_map(|path| Some((path.display().to_string(), read(&path)?)))
+        .collect();
+
+    resolve(&argv, files, |name| std::env::var(name).ok()).unwrap_or_else(|err| err.exit())
+}
+
+/// Resolve the arguments of the command line, completed by the environment
+/// read with `env`, then by the configuration files, the later ones first
+fn resolve<P: CommandFactory + FromArgMatches>(
+    argv: &[OsString],
+    files: Vec<(String, toml::Table)>,
+    env: impl Fn(&str) -> Option<String>,
+) -> Result<(P, Effective), clap::Error> {
+    // The flags also take a value, i.e.: `--td-are-tp=false`, to turn off the
+    // ones of the configuration
+    let command = P::command().mut_args(|arg| {
+        if matches!(arg.get_action(), ArgAction::SetTrue) {
+            arg.action(ArgAction::Set)
+                .num_args(0..=1)
+                .require_equals(true)
+                .default_missing_value("true")
+                .default_value("false")
+                .value_parser(BoolishValueParser::new())
+                .value_name(FLAG)
+        } else {
+            arg
+        }
+    });
+
+    // Only to know which arguments were given, the errors are reported later
+    let given = command
+        .clone()
+        .ignore_errors(true)
+        .try_get_matches_from(argv)?;
+
+    let arguments = command
+        .get_arguments()
+        .filter(|arg| !matches!(arg.get_action(), ArgAction::Help | ArgAction::Version))
+        .cloned()
+        .collect::<Vec<_>>();
+
+    // Later layers take precedence
+    let mut layers = files
+        .into_iter()
+        .map(|(path, table)| (Source::File(path), table))
+        .collect::<Vec<_>>();
+    layers.push((Source::Environment, environment(&arguments, env)));
+
+    let mut values = BTreeMap::new();
+    for (origin, table) in layers {
+        for (key, value) in table {
+            let id = key.replace('-', "_");
+            if arguments.iter().any(|arg| arg.get_id() == id.as_str()) {
+                values.insert(id, (value, origin.clone()));
+            } else {
+                eprintln!("{}", Message::UnknownOption(&origin.to_string(), &key));
+            }
+        }
+    }
+
+    let mut extra: Vec<OsString> = vec![];
+    let mut positionals: Vec<OsString> = vec![];
+    let mut sources = BTreeMap::new();
+    let mut skipped = BTreeMap::new();
+    for arg in &arguments {
+        let id = arg.get_id().as_str();
+        match given.value_source(id) {
+            Some(ValueSource::CommandLine) => {
+                sources.insert(id.to_owned(), Source::CommandLine);
+                continue;
+            }
+            Some(ValueSource::EnvVariable) => {
+                sources.insert(id.to_owned(), Source::Environment);
+                continue;
+            }
+            _ => (),
+        }
+        let Some((value, origin)) = values.remove(id) else {
+            continue;
+        };
+
+        let items = match value {
+            toml::Value::Array(items) => items,
+            value => vec![value],
+        };
+        sources.insert(id.to_owned(), origin);
+
+        // The subcommands don't take the positional arguments
+        if arg.is_positional() && given.subcommand_name().is_some() {
+            skipped.insert(id.to_owned(), items);
+            continue;
+        }
+
+        for item in items {
+            let item = match item {
+                toml::Value::String(text) => text,
+                item => item.to_string(),
+            };
+
+            if arg.is_positional() {
+                positionals.push(item.into());
+            } else {
+                extra.push(format!("--{}={item}", arg.get_long().unwrap()).into());
+            }
+        }
+    }
+
+    let argv = argv
+        .iter()
+        .take(1)
+        .chain(&extra)
+        .chain(&positionals)
+        .chain(argv.iter().skip(1))
+        .collect::<Vec<_>>();
+    let matches = command.try_get_matches_from(argv)?;
+    let parsed = P::from_arg_matches(&matches)?;
+
+    Ok((
+        parsed,
+        Effective {
+            matches,
+            sources,
+            skipped,
+            arguments,
+        },
+    ))
+}
+
+/// Values of the arguments given in the environment, i.e.: `CAL7TOR_TD_ARE_TP=true`
+fn environment(arguments: &[Arg], env: impl Fn(&str) -> Option<String>) -> toml::Table {
+    let mut table = toml::Table::new();
+    for arg in arguments {
+        let id = arg.get_id().as_str();
+        let Some(value) = env(&format!("{ENV_PREFIX}{}", id.to_uppercase())) else {
+            continue;
+        };
+
+        let value = if multiple(arg) {
+            toml::Value::Array(
+                value
+                    .split(',')
+                    .map(|item| toml::Value::String(item.to_owned()))
+                    .collect(),
+            )
+        } else {
+            toml::Value::String(value)
+        };
+        table.insert(id.to_owned(), value);
+    }
+
+    table
+}
+
+/// If the argument is a flag, turned on without value
+fn flag(arg: &Arg) -> bool {
+    arg.get_value_names()
+        .is_some_and(|names| names.len() == 1 && names[0] == FLAG)
+}
+
+/// If the argument takes many values
+fn multiple(arg: &Arg) -> bool {
+    matches!(arg.get_action(), ArgAction::Append)
+        || arg
+            .get_num_args()
+            .is_some_and(|range| range.max_values() > 1)
+}
+
+impl Effective {
+    /// Print the configuration as a configuration file, with the origin of each value
+    pub fn show(&self) {
+        for arg in &self.arguments {
+            let id = arg.get_id().as_str();
+            let mut values = match (self.matches.get_raw(id), self.skipped.get(id)) {
+                (Some(_), _) if flag(arg) => vec![toml::Value::Boolean(self.matches.get_flag(id))],
+                (Some(raw), _) => raw
+                    .map(|value| {
+                        let value = value.to_string_lossy();
+                        if let Ok(number) = value.parse::<i64>() {
+                            toml::Value::Integer(number)
+                        } else {
+                            toml::Value::String(value.into_owned())
+                        }
+                    })
+                    .collect::<Vec<_>>(),
+                (None, Some(values)) => values.clone(),
+                (None, None) => continue,
+            };
+            let value = if multiple(arg) {
+                toml::Value::Array(values)
+            } else {
+                values.remove(0)
+            };
+
+            let source = self
+                .sources
+                .get(id)
+                .map_or_else(|| Message::Default.to_string(), Source::to_string);
+            println!("{} = {value} # {source}", id.replace('_', "-"));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::OsString;
+
+    use clap::{Parser, Subcommand};
+
+    use super::{resolve, Effective};
+    use crate::i18n::Message;
+
+    #[derive(Parser)]
+    struct Args {
+        /// Classes
+        class: Vec<String>,
+        #[clap(long)]
+        year: Option<i32>,
+        #[clap(long)]
+        name: Option<String>,
+        #[clap(long)]
+        td_are_tp: bool,
+        #[clap(long)]
+        week_skip: bool,
+        #[clap(subcommand)]
+        command: Option<Command>,
+    }
+
+    #[derive(Subcommand)]
+    enum Command {
+        Show,
+    }
+
+    fn table(content: &str) -> toml::Table {
+        content.parse().unwrap()
+    }
+
+    fn run(
+        argv: &[&str],
+        files: &[(&str, &str)],
+        env: &[(&str, &str)],
+    ) -> Result<(Args, Effective), clap::Error> {
+        let argv = ["cal7tor"]
+            .iter()
+            .chain(argv)
+            .map(OsString::from)
+            .collect::<Vec<_>>();
+        let files = files
+            .iter()
+            .map(|(path, content)| ((*path).to_owned(), table(content)))
+            .collect();
+
+        resolve(&argv, files, |name| {
+            env.iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| (*value).to_owned())
+        })
+    }
+
+    fn source(effective: &Effective, id: &str) -> Option<String> {
+        effective.sources.get(id).map(ToString::to_string)
+    }
+
+    #[test]
+    fn precedence() {
+        let files = [
+            (
+                "user.toml",
+                "year = 2024\nname = \"user\"\nclass = [\"M2\"]",
+            ),
+            ("cal7tor.toml", "year = 2025\nname = \"project\""),
+        ];
+        let env = [("CAL7TOR_YEAR", "2026")];
+
+        let (args, effective) = run(&["--year", "2027"], &files, &env).unwrap();
+        assert_eq!(args.year, Some(2027));
+        assert_eq!(args.name.as_deref(), Some("project"));
+        assert_eq!(args.class, ["M2"]);
+        assert_eq!(source(&effective, "name").as_deref(), Some("cal7tor.toml"));
+        assert_eq!(source(&effective, "class").as_deref(), Some("user.toml"));
+
+        let (args, _) = run(&[], &files, &env).unwrap();
+        assert_eq!(args.year, Some(2026));
+
+        let (args, _) = run(&[], &files, &[]).unwrap();
+        assert_eq!(args.year, Some(2025));
+
+        let (args, effective) = run(&[], &files[..1], &[]).unwrap();
+        assert_eq!(args.year, Some(2024));
+        assert_eq!(source(&effective, "year").as_deref(), Some("user.toml"));
+
+        let (args, effective) = run(&[], &[], &[]).unwrap();
+        assert_eq!(args.year, None);
+        assert_eq!(source(&effective, "year"), None);
+    }
+
+    #[test]
+    fn flags() {
+        let files = [("cal7tor.toml", "td-are-tp = true\nweek-skip = false")];
+
+        let (args, _) = run(&[], &files, &[]).unwrap();
+        assert!(args.td_are_tp);
+        assert!(!args.week_skip);
+
+        let (args, _) = run(&[], &[], &[("CAL7TOR_WEEK_SKIP", "1")]).unwrap();
+        assert!(args.week_skip);
+
+        let (args, _) = run(&[], &[], &[("CAL7TOR_WEEK_SKIP", "false")]).unwrap();
+        assert!(!args.week_skip);
+
+        // Turned off on the command line
+        let (args, effective) = run(&["--td-are-tp=false"], &files, &[]).unwrap();
+        assert!(!args.td_are_tp);
+        assert_eq!(
+            source(&effective, "td_are_tp"),
+            Some(Message::CommandLine.to_string())
+        );
+        let (args, _) = run(&["--week-skip=no"], &[], &[("CAL7TOR_WEEK_SKIP", "1")]).unwrap();
+        assert!(!args.week_skip);
+
+        // Without value, the next argument isn't taken as one
+        let (args, _) = run(&["--td-are-tp", "M1"], &[], &[]).unwrap();
+        assert!(args.td_are_tp);
+        assert_eq!(args.class, ["M1"]);
+
+        assert!(run(&["--td-are-tp=maybe"], &[], &[]).is_err());
+    }
+
+    #[test]
+    fn classes() {
+        let files = [("cal7tor.toml", "class = [\"M1\", \"M2\"]")];
+
+        let (args, _) = run(&[], &files, &[]).unwrap();
+        assert_eq!(args.class, ["M1", "M2"]);
+
+        let (args, _) = run(&[], &[], &[("CAL7TOR_CLASS", "M1,M2")]).unwrap();
+        assert_eq!(args.class, ["M1", "M2"]);
+
+        // The command line replaces them
+        let (args, _) = run(&["M2"], &files, &[]).unwrap();
+        assert_eq!(args.class, ["M2"]);
+
+        // The subcommands don't take them, but they are still shown
+        let (args, effective) = run(&["show"], &files, &[]).unwrap();
+        assert!(args.class.is_empty());
+        assert!(matches!(args.command, Some(Command::Show)));
+        assert_eq!(effective.skipped["class"].len(), 2);
+    }
+
+    #[test]
+    fn invalid_values() {
+        let files = [("cal7tor.toml", "year = \"next\"")];
+        assert!(run(&[], &files, &[]).is_err());
+        assert!(run(&[], &[], &[("CAL7TOR_YEAR", "next")]).is_err());
+
+        // Given on the command line, the invalid value of the file is unused
+        assert!(run(&["--year", "2026"], &files, &[]).is_ok());
+    }
+}
@@ -0,0 +1,285 @@
+use chrono::Weekday;
+
+use crate::{
+    i18n::{self, Message},
+    ics::import::Occurrence,
+    timetable::models::{Course, Timetable},
+    utils::{format_time_slot, get_entry},
+};
+
+/// Two courses of the same day overlapping each other
+pub struct Conflict<'a> {
+    /// Day of the week
+    pub day: Weekday,
+    /// Course starting first
+    pub first: &'a Course,
+    /// Course starting during the first one
+    pub second: &'a Course,
+}
+
+impl std::fmt::Display for Conflict<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            Message::Overlap(
+                i18n::weekday(self.day),
+                &describe(self.first),
+                &describe(self.second)
+            )
+        )
+    }
+}
+
+/// Find every pair of overlapping items, given their bounds as `[start, end)`
+pub fn overlaps<T, K: Ord>(items: &[T], bounds: impl Fn(&T) -> (K, K)) -> Vec<(usize, usize)> {
+    let mut sorted = items.iter().map(&bounds).enumerate().collect::<Vec<_>>();
+    sorted.sort_by(|a, b| a.1 .0.cmp(&b.1 .0));
+
+    let mut pairs = vec![];
+    for (position, (i, (_, end))) in sorted.iter().enumerate() {
+        // Only the items starting before the end of this one can overlap it
+        for (j, (start, _)) in &sorted[position + 1..] {
+            if start >= end {
+                break;
+            }
+            pairs.push((*i, *j));
+        }
+    }
+
+    pairs
+}
+
+/// Find the conflicts between the courses of the timetable, the slots of the same
+/// course overlapping each other are alternatives, not conflicts
+pub fn timetable(timetable: &Timetable) -> Vec<Conflict<'_>> {
+    timetable
+        .1
+         .1
+        .iter()
+        .flat_map(|day| {
+            let courses = day.courses.iter().flatten().collect::<Vec<_>>();
+            overlaps(&courses, |course| (course.start, course.end))
+                .into_iter()
+                .filter(|(i, j)| get_entry(courses[*i]) != get_entry(courses[*j]))
+                .map(|(i, j)| Conflict {
+                    day: day.weekday,
+                    first: courses[i],
+                    second: courses[j],
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// If the conflict remains whichever slots are kept, none of its courses
+/// having another slot in the timetable
+pub fn unavoidable(timetable: &Timetable, conflict: &Conflict) -> bool {
+    let slots = |course: &Course| {
+        timetable
+            .1
+             .1
+            .iter()
+            .flat_map(|day| day.courses.iter().flatten())
+            .filter(|other| get_entry(other) == get_entry(course))
+            .count()
+    };
+
+    slots(conflict.first) == 1 && slots(conflict.second) == 1
+}
+
+/// Find the imported events overlapping a course
+pub fn imported<'a>(
+    courses: &'a [Course],
+    events: &'a [Occurrence],
+) -> Vec<(&'a Course, &'a Occurrence)> {
+    let bounds = courses
+        .iter()
+        .map(|course| {
+            (
+                course.dtstart.unwrap().naive_utc(),
+                course.dtend.unwrap().naive_utc(),
+            )
+        })
+        .chain(events.iter().map(|event| (event.start, event.end)))
+        .collect::<Vec<_>>();
+
+    // Only keep the pairs made of a course and an event
+    overlaps(&bounds, |bounds| *bounds)
+        .into_iter()
+        .filter_map(|(i, j)| {
+            let (course, event) = (i.min(j), i.max(j));
+            (course < courses.len() && event >= courses.len())
+                .then(|| (&courses[course], &events[event - courses.len()]))
+        })
+        .collect()
+}
+
+/// Print the overlaps with the imported events, returns true if there was any
+pub fn report_imported(overlaps: &[(&Course, &Occurrence)]) -> bool {
+    if overlaps.is_empty() {
+        return false;
+    }
+
+    eprintln!("{}", Message::ImportedOverlap);
+    for (course, event) in overlaps {
+        let date = event.start.format("%d/%m/%Y").to_string();
+        eprintln!(
+            "  {}",
+            Message::Overlap(&date, &event.summary, &describe(course))
+        );
+    }
+
+    true
+}
+
+/// Courses of the same day overlapping the given course
+pub fn overlapping<'a>(
+    course: &Course,
+    others: impl Iterator<Item = &'a Course>,
+) -> Vec<&'a Course> {
+    others
+        .filter(|other| other.start < course.end && course.start < other.end)
+        .collect()
+}
+
+/// Print the conflicts, returns true if there was any
+pub fn report(conflicts: &[Conflict]) -> bool {
+    if conflicts.is_empty() {
+        return false;
+    }
+
+    eprintln!("{}", Message::CoursesOverlap);
+    for conflict in conflicts {
+        eprintln!("  {conflict}");
+    }
+
+    true
+}
+
+/// Short description of a course
+pub fn describe(course: &Course) -> String {
+    format!(
+        "{} - {} ({})",
+        course
+            .category
+            .iter()
+            .map(std::string::ToString::to_string)
+            .collect::<Vec<String>>()
+            .join("/"),
+        course.name,
+        format_time_slot(course.start, course.end)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Weekday;
+
+    use super::{overlapping, overlaps, timetable, unavoidable};
+    use crate::{
+        filter,
+        timetable::{
+            grid,
+            models::{self, Category, Course, Day},
+        },
+    };
+
+    fn course(name: &str, hours: (u32, u32)) -> Course {
+        models::course(name, Category::Cours, hours)
+    }
+
+    #[test]
+    fn touching_intervals_dont_overlap() {
+        assert!(overlaps(&[(8, 10), (10, 12)], |bounds| *bounds).is_empty());
+
+        let first = course("A", (8, 10));
+        let others = [course("B", (10, 12)), course("C", (6, 8))];
+        assert!(overlapping(&first, others.iter()).is_empty());
+    }
+
+    #[test]
+    fn nested_intervals_overlap() {
+        assert_eq!(overlaps(&[(8, 14), (9, 10)], |bounds| *bounds), [(0, 1)]);
+
+        let outer = course("A", (8, 14));
+        let inner = [course("B", (9, 10))];
+        assert_eq!(overlapping(&outer, inner.iter()).len(), 1);
+        assert_eq!(overlapping(&inner[0], [outer.clone()].iter()).len(), 1);
+    }
+
+    #[test]
+    fn every_overlapping_pair_is_found() {
+        // Unsorted on purpose, C overlaps both A and B which don't overlap
+        let items = [(10, 12), (8, 10), (9, 11), (13, 14)];
+
+        let mut pairs = overlaps(&items, |bounds| *bounds)
+            .into_iter()
+            .map(|(i, j)| (i.min(j), i.max(j)))
+            .collect::<Vec<_>>();
+        pairs.sort_unstable();
+
+        assert_eq!(pairs, [(0, 2), (1, 2)]);
+
+        let courses = [
+            course("A", (10, 12)),
+            course("B", (8, 10)),
+            course("D", (13, 14)),
+        ];
+        let names = overlapping(&course("C", (9, 11)), courses.iter())
+            .iter()
+            .map(|course| course.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["A", "B"]);
+    }
+
+    #[test]
+    fn alternatives_kept_without_prompt() {
+        let td = |group: &str, hours| Course {
+            data: Some(group.to_owned()),
+            ..models::course("Compilation", Category::TD, hours)
+        };
+        let days = vec![
+            Day {
+                weekday: Weekday::Mon,
+                courses: vec![
+                    Some(course("Compilation", (8, 10))),
+                    Some(td("Groupe 1", (9, 11))),
+                    Some(td("Groupe 2", (9, 11))),
+                ],
+            },
+            Day {
+                weekday: Weekday::Tue,
+                courses: vec![
+                    Some(course("Réseau", (8, 10))),
+                    Some(course("Algo", (9, 11))),
+                ],
+            },
+        ];
+
+        // Every group is kept without anyone to choose
+        let filtered = filter::timetable((grid::slots(&days), (1, days)), false, None, false);
+        let conflicts = timetable(&filtered);
+
+        let found = conflicts
+            .iter()
+            .map(|conflict| {
+                (
+                    conflict.first.name.as_str(),
+                    conflict.second.name.as_str(),
+                    unavoidable(&filtered, conflict),
+                )
+            })
+            .collect::<Vec<_>>();
+        // The groups don't conflict with each other, and one of them
+        // can still be dropped, unlike the courses of Tuesday
+        assert_eq!(
+            found,
+            [
+                ("Compilation", "Compilation", false),
+                ("Compilation", "Compilation", false),
+                ("Réseau", "Algo", true)
+            ]
+        );
+    }
+}
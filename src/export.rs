@@ -0,0 +1,195 @@
+use std::collections::BTreeMap;
+
+use chrono::{Datelike, NaiveDate, NaiveTime, Timelike, Weekday};
+use clap::ValueEnum;
+
+use crate::{
+    ics::{categories, import::Imported, Metadata},
+    timetable::models::Course,
+    utils::file,
+};
+
+pub mod csv;
+pub mod json;
+pub mod markdown;
+pub mod org;
+
+/// What the exporters need besides the courses
+pub struct Context<'a> {
+    /// Properties of the calendar
+    pub metadata: &'a Metadata,
+    /// Include the timezone in the dates
+    pub with_tz: bool,
+    /// Events merged with the courses
+    pub imported: &'a Imported,
+}
+
+/// Format of an exported calendar
+pub trait Exporter {
+    /// Extensions of the files, the first one is the usual one
+    fn extensions(&self) -> &'static [&'static str];
+
+    /// Render the courses
+    fn render(&self, courses: Vec<Course>, context: &Context) -> String;
+}
+
+/// Formats the calendar can be exported to
+#[derive(Clone, Copy, ValueEnum)]
+pub enum Format {
+    /// iCalendar
+    Ics,
+    /// Courses as JSON
+    Json,
+    /// Courses as CSV, one per line
+    Csv,
+    /// Courses as an HTML page
+    Html,
+    /// Org-mode agenda
+    Org,
+    /// Markdown weekly table
+    Markdown,
+}
+
+impl Format {
+    /// Exporter of the format
+    pub fn exporter(self) -> Box<dyn Exporter> {
+        match self {
+            Self::Ics => Box::new(crate::ics::Ics),
+            Self::Json => Box::new(json::Json),
+            Self::Csv => Box::new(csv::Csv),
+            Self::Html => Box::new(crate::html::Html),
+            Self::Org => Box::new(org::Org),
+            Self::Markdown => Box::new(markdown::Markdown),
+        }
+    }
+
+    /// Find the format from the extension of the file
+    pub fn from_filename(filename: &str) -> Option<Self> {
+        let extension = std::path::Path::new(filename).extension()?;
+
+        Self::value_variants().iter().copied().find(|format| {
+            format
+                .exporter()
+                .extensions()
+                .iter()
+                .any(|ext| extension.eq_ignore_ascii_case(ext))
+        })
+    }
+}
+
+/// Add the extension to a file name without one, the standard output `-` is kept
+pub fn with_extension(filename: &str, extension: &str) -> String {
+    if filename == "-" || std::path::Path::new(filename).extension().is_some() {
+        filename.to_owned()
+    } else {
+        format!("{filename}.{extension}")
+    }
+}
+
+/// Export the courses to the file, or to the standard output with `-`
+pub fn export(
+    exporter: &dyn Exporter,
+    courses: Vec<Course>,
+    filename: &str,
+    context: &Context,
+) -> std::io::Result<()> {
+    file::write(filename, &exporter.render(courses, context))
+}
+
+/// A course given every week at the same time
+pub struct Series<'a> {
+    /// First course of the series
+    pub course: &'a Course,
+    /// Day of the week
+    pub weekday: Weekday,
+    /// Start time
+    pub start: NaiveTime,
+    /// End time
+    pub end: NaiveTime,
+    /// Dates of the courses, sorted
+    pub dates: Vec<NaiveDate>,
+}
+
+/// Group the courses in weekly series, sorted by day and time
+pub fn series(courses: &[Course]) -> Vec<Series<'_>> {
+    let mut series: BTreeMap<_, Series> = BTreeMap::new();
+    for course in courses {
+        let start = course.dtstart.unwrap().naive_utc();
+        let end = course.dtend.unwrap().naive_utc();
+        let key = (
+            start.weekday().num_days_from_monday(),
+            start.time(),
+            end.time(),
+            course.name.clone(),
+            categories(course),
+            course.class.clone(),
+        );
+
+        series
+            .entry(key)
+            .or_insert_with(|| Series {
+                course,
+                weekday: start.weekday(),
+                start: start.time(),
+                end: end.time(),
+                dates: vec![],
+            })
+            .dates
+            .push(start.date());
+    }
+
+    series
+        .into_values()
+        .map(|mut series| {
+            series.dates.sort();
+            series
+        })
+        .collect()
+}
+
+/// Format a time, i.e.: 8h30
+pub fn hour(time: NaiveTime) -> String {
+    format!("{}h{:02}", time.hour(), time.minute())
+}
+
+/// Courses to export in the tests: a weekly course skipping a week, with
+/// characters to escape, and a single TD
+#[cfg(test)]
+pub(crate) fn courses() -> Vec<Course> {
+    use chrono::{TimeZone, Utc};
+
+    use crate::timetable::models::{self, Category};
+
+    let at = |(month, day), (hour, minute)| Utc.with_ymd_and_hms(2026, month, day, hour, minute, 0);
+    let course = |day, (start, end)| Course {
+        room: "Amphi 1".to_owned(),
+        professor: Some("Ada".to_owned()),
+        data: Some("Groupe 1".to_owned()),
+        dtstart: at(day, start).single(),
+        dtend: at(day, end).single(),
+        ..models::course("Algo | Graphes, \"avancé\"", Category::Cours, (8, 10))
+    };
+
+    vec![
+        course((9, 14), ((8, 30), (10, 30))),
+        course((9, 28), ((8, 30), (10, 30))),
+        Course {
+            room: "2001".to_owned(),
+            dtstart: at((9, 17), (13, 0)).single(),
+            dtend: at((9, 17), (15, 0)).single(),
+            ..models::course("Réseau", Category::TD, (13, 15))
+        },
+    ]
+}
+
+/// Render the courses of the tests with the exporter
+#[cfg(test)]
+pub(crate) fn render(exporter: &dyn Exporter) -> String {
+    let context = Context {
+        metadata: &crate::ics::metadata(),
+        with_tz: true,
+        imported: &Imported::default(),
+    };
+
+    exporter.render(courses(), &context)
+}
@@ -0,0 +1,74 @@
+use std::fmt::Write;
+
+use super::{Context, Exporter};
+use crate::{ics::categories, timetable::models::Course};
+
+/// Courses as CSV, one per line
+pub struct Csv;
+
+impl Exporter for Csv {
+    fn extensions(&self) -> &'static [&'static str] {
+        &["csv"]
+    }
+
+    fn render(&self, courses: Vec<Course>, _: &Context) -> String {
+        let mut table = String::from("date,start,end,category,name,class,room,professor,group\n");
+
+        for course in &courses {
+            let start = course.dtstart.unwrap();
+            let end = course.dtend.unwrap();
+
+            let fields = [
+                start.format("%Y-%m-%d").to_string(),
+                start.format("%H:%M").to_string(),
+                end.format("%H:%M").to_string(),
+                categories(course),
+                course.name.clone(),
+                course.class.clone(),
+                course.room.clone(),
+                course.professor.clone().unwrap_or_default(),
+                course.data.clone().unwrap_or_default(),
+            ];
+
+            let _ = writeln!(
+                table,
+                "{}",
+                fields
+                    .iter()
+                    .map(|field| escape(field))
+                    .collect::<Vec<_>>()
+                    .join(",")
+            );
+        }
+
+        table
+    }
+}
+
+/// Quote the field when needed, see RFC 4180
+fn escape(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Csv;
+    use crate::export::render;
+
+    #[test]
+    fn golden() {
+        assert_eq!(
+            render(&Csv),
+            concat!(
+                "date,start,end,category,name,class,room,professor,group\n",
+                "2026-09-14,08:30,10:30,Cours,\"Algo | Graphes, \"\"avancé\"\"\",M1,Amphi 1,Ada,Groupe 1\n",
+                "2026-09-28,08:30,10:30,Cours,\"Algo | Graphes, \"\"avancé\"\"\",M1,Amphi 1,Ada,Groupe 1\n",
+                "2026-09-17,13:00,15:00,TD,Réseau,M1,2001,,\n"
+            )
+        );
+    }
+}
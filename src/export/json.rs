@@ -0,0 +1,101 @@
+use chrono::NaiveDateTime;
+use serde::Serialize;
+
+use super::{Context, Exporter};
+use crate::timetable::models::{Category, Course};
+
+/// Courses as JSON, with their local start and end
+pub struct Json;
+
+#[derive(Serialize)]
+struct Calendar<'a> {
+    name: &'a str,
+    description: Option<&'a str>,
+    events: Vec<Event<'a>>,
+}
+
+#[derive(Serialize)]
+struct Event<'a> {
+    category: &'a [Category],
+    name: &'a str,
+    class: &'a str,
+    room: &'a str,
+    professor: Option<&'a str>,
+    group: Option<&'a str>,
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+}
+
+impl Exporter for Json {
+    fn extensions(&self) -> &'static [&'static str] {
+        &["json"]
+    }
+
+    fn render(&self, courses: Vec<Course>, context: &Context) -> String {
+        let calendar = Calendar {
+            name: &context.metadata.name,
+            description: context.metadata.description.as_deref(),
+            events: courses
+                .iter()
+                .map(|course| Event {
+                    category: &course.category,
+                    name: &course.name,
+                    class: &course.class,
+                    room: &course.room,
+                    professor: course.professor.as_deref(),
+                    group: course.data.as_deref(),
+                    start: course.dtstart.unwrap().naive_utc(),
+                    end: course.dtend.unwrap().naive_utc(),
+                })
+                .collect(),
+        };
+
+        serde_json::to_string_pretty(&calendar).unwrap() + "\n"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::Json;
+    use crate::export::render;
+
+    #[test]
+    fn golden() {
+        let algo = |day| {
+            json!({
+                "category": ["Cours"],
+                "name": "Algo | Graphes, \"avancé\"",
+                "class": "M1",
+                "room": "Amphi 1",
+                "professor": "Ada",
+                "group": "Groupe 1",
+                "start": format!("2026-09-{day}T08:30:00"),
+                "end": format!("2026-09-{day}T10:30:00"),
+            })
+        };
+
+        assert_eq!(
+            serde_json::from_str::<Value>(&render(&Json)).unwrap(),
+            json!({
+                "name": "M1",
+                "description": null,
+                "events": [
+                    algo(14),
+                    algo(28),
+                    {
+                        "category": ["TD"],
+                        "name": "Réseau",
+                        "class": "M1",
+                        "room": "2001",
+                        "professor": null,
+                        "group": null,
+                        "start": "2026-09-17T13:00:00",
+                        "end": "2026-09-17T15:00:00",
+                    },
+                ],
+            })
+        );
+    }
+}
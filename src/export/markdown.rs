@@ -0,0 +1,133 @@
+use std::fmt::Write;
+
+use super::{hour, series, Context, Exporter};
+use crate::{
+    i18n::{self, Message},
+    ics::categories,
+    timetable::models::Course,
+};
+
+/// Markdown weekly table, with the days as columns
+pub struct Markdown;
+
+impl Exporter for Markdown {
+    fn extensions(&self) -> &'static [&'static str] {
+        &["md", "markdown"]
+    }
+
+    fn render(&self, courses: Vec<Course>, context: &Context) -> String {
+        let metadata = context.metadata;
+        let series = series(&courses);
+
+        let mut table = format!("# {}\n\n", escape(&metadata.name));
+        if let Some(description) = &metadata.description {
+            let _ = writeln!(table, "{}\n", escape(description));
+        }
+
+        let dates = series.iter().flat_map(|series| &series.dates);
+        if let (Some(first), Some(last)) = (dates.clone().min(), dates.max()) {
+            let _ = writeln!(
+                table,
+                "{}\n",
+                Message::Period(&first.format("%d/%m/%Y"), &last.format("%d/%m/%Y"))
+            );
+        }
+
+        // Only the days and the time slots with courses
+        let mut days = series
+            .iter()
+            .map(|series| series.weekday)
+            .collect::<Vec<_>>();
+        days.sort_by_key(chrono::Weekday::num_days_from_monday);
+        days.dedup();
+        let mut slots = series
+            .iter()
+            .map(|series| (series.start, series.end))
+            .collect::<Vec<_>>();
+        slots.sort();
+        slots.dedup();
+
+        let _ = writeln!(
+            table,
+            "| {} | {} |",
+            Message::Schedule,
+            days.iter()
+                .map(|day| i18n::weekday(*day))
+                .collect::<Vec<_>>()
+                .join(" | ")
+        );
+        let _ = writeln!(table, "|---|{}", "---|".repeat(days.len()));
+
+        for (start, end) in slots {
+            let cells = days
+                .iter()
+                .map(|day| {
+                    series
+                        .iter()
+                        .filter(|series| {
+                            series.weekday == *day && series.start == start && series.end == end
+                        })
+                        .map(|series| cell(series.course))
+                        .collect::<Vec<_>>()
+                        .join("<br>")
+                })
+                .collect::<Vec<_>>();
+
+            let _ = writeln!(
+                table,
+                "| {}-{} | {} |",
+                hour(start),
+                hour(end),
+                cells.join(" | ")
+            );
+        }
+
+        table
+    }
+}
+
+/// Content of the cell of a course
+fn cell(course: &Course) -> String {
+    let mut cell = format!(
+        "**{} - {}** ({})",
+        escape(&categories(course)),
+        escape(&course.name),
+        escape(&course.class)
+    );
+    if !course.room.is_empty() {
+        let _ = write!(cell, "<br>{}", escape(&course.room));
+    }
+    if let Some(professor) = &course.professor {
+        let _ = write!(cell, "<br>{}", escape(professor));
+    }
+
+    cell
+}
+
+/// Escape the text to put it in a table
+fn escape(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Markdown;
+    use crate::export::render;
+
+    #[test]
+    fn golden() {
+        assert_eq!(
+            render(&Markdown),
+            concat!(
+                "# M1\n",
+                "\n",
+                "Du 14/09/2026 au 28/09/2026\n",
+                "\n",
+                "| Horaire | Lundi | Jeudi |\n",
+                "|---|---|---|\n",
+                "| 8h30-10h30 | **Cours - Algo \\| Graphes, \"avancé\"** (M1)<br>Amphi 1<br>Ada |  |\n",
+                "| 13h00-15h00 |  | **TD - Réseau** (M1)<br>2001 |\n"
+            )
+        );
+    }
+}
@@ -0,0 +1,127 @@
+use std::fmt::Write;
+
+use chrono::{Duration, NaiveDate, NaiveTime};
+
+use super::{series, Context, Exporter};
+use crate::{ics::categories, timetable::models::Course};
+
+/// Org-mode agenda, with one heading per weekly course
+pub struct Org;
+
+impl Exporter for Org {
+    fn extensions(&self) -> &'static [&'static str] {
+        &["org"]
+    }
+
+    fn render(&self, courses: Vec<Course>, context: &Context) -> String {
+        let metadata = context.metadata;
+
+        let mut agenda = format!("#+TITLE: {}\n", metadata.name);
+        if let Some(description) = &metadata.description {
+            let _ = writeln!(agenda, "#+DESCRIPTION: {description}");
+        }
+
+        for series in series(&courses) {
+            let course = series.course;
+            let first = series.dates[0];
+            let last = series.dates[series.dates.len() - 1];
+
+            let _ = writeln!(
+                agenda,
+                "\n* {} - {}\n  SCHEDULED: {}",
+                categories(course),
+                course.name,
+                timestamp(
+                    first,
+                    series.start,
+                    series.end,
+                    (series.dates.len() > 1).then_some("+1w")
+                )
+            );
+
+            agenda.push_str("  :PROPERTIES:\n");
+            let _ = writeln!(agenda, "  :CATEGORY: {}", categories(course));
+            let _ = writeln!(agenda, "  :CLASS:    {}", course.class);
+            let _ = writeln!(agenda, "  :LOCATION: {}", course.room);
+            if let Some(professor) = &course.professor {
+                let _ = writeln!(agenda, "  :PROFESSOR: {professor}");
+            }
+            if series.dates.len() > 1 {
+                // Repeaters have no end, nor exceptions
+                let _ = writeln!(agenda, "  :LAST:     <{}>", last.format("%Y-%m-%d %a"));
+                let skipped = skipped(&series.dates);
+                if !skipped.is_empty() {
+                    let _ = writeln!(agenda, "  :SKIPPED:  {}", skipped.join(" "));
+                }
+            }
+            agenda.push_str("  :END:\n");
+
+            if let Some(data) = course.data.as_deref().filter(|data| !data.is_empty()) {
+                let _ = writeln!(agenda, "  {data}");
+            }
+        }
+
+        agenda
+    }
+}
+
+/// Org-mode active timestamp, i.e.: `<2026-09-14 Mon 08:30-10:00 +1w>`
+fn timestamp(date: NaiveDate, start: NaiveTime, end: NaiveTime, repeater: Option<&str>) -> String {
+    format!(
+        "<{} {}-{}{}>",
+        date.format("%Y-%m-%d %a"),
+        start.format("%H:%M"),
+        end.format("%H:%M"),
+        repeater
+            .map(|repeater| format!(" {repeater}"))
+            .unwrap_or_default()
+    )
+}
+
+/// Weeks without the course between its first and last dates
+fn skipped(dates: &[NaiveDate]) -> Vec<String> {
+    dates
+        .windows(2)
+        .flat_map(|pair| {
+            let weeks = (pair[1] - pair[0]).num_weeks();
+            (1..weeks).map(move |week| pair[0] + Duration::weeks(week))
+        })
+        .map(|date| format!("<{}>", date.format("%Y-%m-%d %a")))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Org;
+    use crate::export::render;
+
+    #[test]
+    fn golden() {
+        assert_eq!(
+            render(&Org),
+            concat!(
+                "#+TITLE: M1\n",
+                "\n",
+                "* Cours - Algo | Graphes, \"avancé\"\n",
+                "  SCHEDULED: <2026-09-14 Mon 08:30-10:30 +1w>\n",
+                "  :PROPERTIES:\n",
+                "  :CATEGORY: Cours\n",
+                "  :CLASS:    M1\n",
+                "  :LOCATION: Amphi 1\n",
+                "  :PROFESSOR: Ada\n",
+                "  :LAST:     <2026-09-28 Mon>\n",
+                "  :SKIPPED:  <2026-09-21 Mon>\n",
+                "  :END:\n",
+                "  Groupe 1\n",
+                "\n",
+                "* TD - Réseau\n",
+                "  SCHEDULED: <2026-09-17 Thu 13:00-15:00>\n",
+                "  :PROPERTIES:\n",
+                "  :CATEGORY: TD\n",
+                "  :CLASS:    M1\n",
+                "  :LOCATION: 2001\n",
+                "  :END:\n"
+            )
+        );
+    }
+}
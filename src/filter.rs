@@ -1,16 +1,28 @@
-use dialoguer::MultiSelect;
+use std::collections::BTreeMap;
 
+use chrono::Weekday;
+use dialoguer::{MultiSelect, Select};
+
+use crate::conflicts::overlapping;
+use crate::i18n::Message;
+use crate::solver::{solve, Preference};
 use crate::timetable::models::Category;
+use crate::timetable::models::Course;
 use crate::timetable::models::Timetable;
 use crate::utils::get_count;
 use crate::utils::get_entry;
 use crate::utils::get_entry_nocat;
 use crate::utils::get_selection;
 
-const DISCLAIMER: &str = "(selection avec ESPACE, ENTRER pour valider)";
-
-/// Filter the timetable
-pub fn timetable(timetable: Timetable, merge_td_tp: bool) -> Timetable {
+/// Filter the timetable, proposing combinations of TD/TP
+/// based on the preferences when `solver` is given.
+/// When not `interactive`, everything is kept unless the solver finds a combination
+pub fn timetable(
+    timetable: Timetable,
+    merge_td_tp: bool,
+    solver: Option<&[Preference]>,
+    interactive: bool,
+) -> Timetable {
     let mut my_timetable = timetable;
 
     /* Note on Cours/TD:
@@ -20,40 +32,51 @@ pub fn timetable(timetable: Timetable, merge_td_tp: bool) -> Timetable {
      * it's probably because there's an alternation between course
      * and TD and no other choice is possible. */
 
-    choice(&mut my_timetable);
-    courses(&mut my_timetable);
-    tdtp(&mut my_timetable, merge_td_tp);
+    choice(&mut my_timetable, interactive);
+    courses(&mut my_timetable, interactive);
+    tdtp(&mut my_timetable, merge_td_tp, solver, interactive);
+
+    // Days without any course left aren't shown
+    my_timetable
+        .1
+         .1
+        .retain(|day| day.courses.iter().any(Option::is_some));
 
     my_timetable
 }
 
 /// Exclude some courses
-fn choice(timetable: &mut Timetable) {
+fn choice(timetable: &mut Timetable, interactive: bool) {
     let mut multiselected = vec![];
     timetable.1 .1.iter().for_each(|day| {
         day.courses.iter().for_each(|course_opt| {
             if let Some(course) = course_opt {
-                if !multiselected.contains(&course.name) {
-                    multiselected.push(course.name.clone());
+                let subject = subject(course);
+                if !multiselected.contains(&subject) {
+                    multiselected.push(subject);
                 }
             }
         });
     });
 
-    let defaults = vec![true; multiselected.len()];
-    let selections = MultiSelect::new()
-        .with_prompt(format!("Choisis tes matières {DISCLAIMER}"))
-        .items(&multiselected[..])
-        .defaults(&defaults[..])
-        .interact()
-        .unwrap();
+    let selections = if interactive {
+        let defaults = vec![true; multiselected.len()];
+        MultiSelect::new()
+            .with_prompt(Message::ChooseSubjects.to_string())
+            .items(&multiselected[..])
+            .defaults(&defaults[..])
+            .interact()
+            .unwrap()
+    } else {
+        (0..multiselected.len()).collect()
+    };
 
     for day in &mut timetable.1 .1 {
         day.courses.retain(|course_opt| {
             if let Some(course) = course_opt {
                 // Remove courses not followed
                 for i in &selections {
-                    if course.name == multiselected[*i] {
+                    if subject(course) == multiselected[*i] {
                         return true;
                     }
                 }
@@ -64,26 +87,47 @@ fn choice(timetable: &mut Timetable) {
     }
 }
 
+/// Subject's name showed to the users
+fn subject(course: &Course) -> String {
+    format!("[{}] {}", course.class, course.name)
+}
+
 /// Filter the multiple courses
-fn courses(timetable: &mut Timetable) {
+fn courses(timetable: &mut Timetable, interactive: bool) {
     let entry_getter = get_entry;
 
     // List of courses and Counter of how much they appears
     // to know if multiples slots are available
     let (mut courses, counts) = get_count(timetable, &[Category::Cours], entry_getter);
 
+    // Courses with only one slot are always kept
+    let kept = courses
+        .iter()
+        .filter(|course| *counts.get(&entry_getter(course.0)).unwrap() == 1)
+        .copied()
+        .collect::<Vec<_>>();
+
     // Keep only elements who have multiples slots
     courses.retain(|course| *counts.get(&entry_getter(course.0)).unwrap() > 1);
 
-    let mut multiselected: Vec<String> = courses.iter().map(get_selection).collect();
+    let mut multiselected: Vec<(String, String)> = courses
+        .iter()
+        .map(|course| (get_selection(course), label(course, &kept)))
+        .collect();
     multiselected.sort();
 
     let mut selections = vec![];
-    if !multiselected.is_empty() {
+    if !interactive {
+        selections = (0..multiselected.len()).collect();
+    } else if !multiselected.is_empty() {
         let defaults = vec![false; multiselected.len()];
+        let labels = multiselected
+            .iter()
+            .map(|(_, label)| label)
+            .collect::<Vec<_>>();
         selections = MultiSelect::new()
-            .with_prompt(format!("Choisis tes horaires de Cours {DISCLAIMER}"))
-            .items(&multiselected[..])
+            .with_prompt(Message::ChooseCourses.to_string())
+            .items(&labels[..])
             .defaults(&defaults[..])
             .interact()
             .unwrap();
@@ -107,7 +151,7 @@ fn courses(timetable: &mut Timetable) {
 
                 // Keep only chosen courses if multiple was available
                 for i in &selections {
-                    if get_selection(&(course, day.name.clone())) == multiselected[*i] {
+                    if get_selection(&(course, day.weekday)) == multiselected[*i].0 {
                         return true;
                     }
                 }
@@ -119,7 +163,7 @@ fn courses(timetable: &mut Timetable) {
 }
 
 /// Filter the multiples TD/TP
-fn tdtp(timetable: &mut Timetable, merge: bool) {
+fn tdtp(timetable: &mut Timetable, merge: bool, solver: Option<&[Preference]>, interactive: bool) {
     // If we differentiate TD from TP
     let entry_getter = if merge { get_entry_nocat } else { get_entry };
 
@@ -130,18 +174,64 @@ fn tdtp(timetable: &mut Timetable, merge: bool) {
     // Keep only elements who have multiples TD/TP
     td_or_tp.retain(|course| *counts.get(&entry_getter(course.0)).unwrap() > 1);
 
-    let mut multiselected: Vec<String> = td_or_tp.iter().map(get_selection).collect();
+    // Everything else is already chosen
+    let kept = timetable
+        .1
+         .1
+        .iter()
+        .flat_map(|day| {
+            day.courses
+                .iter()
+                .flatten()
+                .map(|course| (course, day.weekday))
+        })
+        .filter(|(course, _)| !td_or_tp.iter().any(|(c, _)| std::ptr::eq(*c, *course)))
+        .collect::<Vec<_>>();
+
+    let mut multiselected: Vec<(String, String)> = td_or_tp
+        .iter()
+        .map(|course| (get_selection(course), label(course, &kept)))
+        .collect();
     multiselected.sort();
 
-    let mut selections = vec![];
+    let mut chosen = vec![];
     if !multiselected.is_empty() {
-        let defaults = vec![false; multiselected.len()];
-        selections = MultiSelect::new()
-            .with_prompt(format!("Choisis tes horaires de TD/TP {DISCLAIMER}"))
-            .items(&multiselected[..])
-            .defaults(&defaults[..])
-            .interact()
-            .unwrap();
+        if let Some(proposal) = solver.and_then(|preferences| {
+            // Group the slots by TD/TP
+            let mut groups = BTreeMap::new();
+            for course in &td_or_tp {
+                groups
+                    .entry(entry_getter(course.0))
+                    .or_insert_with(Vec::new)
+                    .push(*course);
+            }
+
+            propose(
+                &kept,
+                &groups.into_values().collect::<Vec<_>>(),
+                preferences,
+                interactive,
+            )
+        }) {
+            chosen = proposal;
+        } else if !interactive {
+            chosen = multiselected.into_iter().map(|(key, _)| key).collect();
+        } else {
+            let defaults = vec![false; multiselected.len()];
+            let labels = multiselected
+                .iter()
+                .map(|(_, label)| label)
+                .collect::<Vec<_>>();
+            chosen = MultiSelect::new()
+                .with_prompt(Message::ChooseTdTp.to_string())
+                .items(&labels[..])
+                .defaults(&defaults[..])
+                .interact()
+                .unwrap()
+                .into_iter()
+                .map(|i| multiselected[i].0.clone())
+                .collect();
+        }
     }
 
     // Keep only wanted courses
@@ -159,10 +249,8 @@ fn tdtp(timetable: &mut Timetable, merge: bool) {
                 }
 
                 // Keep only chosen TD/TP if multiple was available
-                for i in &selections {
-                    if get_selection(&(course, day.name.clone())) == multiselected[*i] {
-                        return true;
-                    }
+                if chosen.contains(&get_selection(&(course, day.weekday))) {
+                    return true;
                 }
             }
 
@@ -170,3 +258,79 @@ fn tdtp(timetable: &mut Timetable, merge: bool) {
         });
     }
 }
+
+/// Let the user pick one of the best combinations of slots found by the solver,
+/// returns `None` when the user prefers to choose manually.
+/// When not `interactive`, the best combination is picked
+fn propose(
+    kept: &[(&Course, Weekday)],
+    groups: &[Vec<(&Course, Weekday)>],
+    preferences: &[Preference],
+    interactive: bool,
+) -> Option<Vec<String>> {
+    let solutions = solve(kept, groups, preferences);
+    if solutions.is_empty() {
+        eprintln!("{}", Message::NoCombination);
+        return None;
+    }
+
+    let mut items = solutions
+        .iter()
+        .map(|solution| {
+            format!(
+                "{} {}",
+                solution
+                    .picks
+                    .iter()
+                    .zip(groups)
+                    .map(|(pick, group)| get_selection(&group[*pick]))
+                    .collect::<Vec<_>>()
+                    .join(" | "),
+                Message::Days(solution.days)
+            )
+        })
+        .collect::<Vec<_>>();
+    items.push(Message::ChooseManually.to_string());
+
+    let selection = if interactive {
+        Select::new()
+            .with_prompt(Message::ChooseCombination.to_string())
+            .items(&items[..])
+            .default(0)
+            .interact()
+            .unwrap()
+    } else {
+        0
+    };
+
+    solutions.get(selection).map(|solution| {
+        solution
+            .picks
+            .iter()
+            .zip(groups)
+            .map(|(pick, group)| get_selection(&group[*pick]))
+            .collect()
+    })
+}
+
+/// Name showed to the users, with a warning when the slot overlaps a kept course
+fn label(entry: &(&Course, Weekday), kept: &[(&Course, Weekday)]) -> String {
+    let selection = get_selection(entry);
+
+    let conflicts = overlapping(
+        entry.0,
+        kept.iter()
+            .filter(|(_, day)| *day == entry.1)
+            .map(|(course, _)| *course),
+    );
+    if conflicts.is_empty() {
+        return selection;
+    }
+
+    let names = conflicts
+        .iter()
+        .map(|course| course.name.as_str())
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!("{selection} {}", Message::OverlapsWith(&names))
+}
@@ -0,0 +1,396 @@
+use std::fmt::Write;
+
+use chrono::{Datelike, NaiveTime};
+
+use crate::export::{hour, with_extension, Context, Exporter};
+use crate::i18n::{self, Message};
+use crate::timetable::{
+    grid::{assign_lanes, rows},
+    models::{Course, Timetable},
+};
+use crate::utils::{file, format_time_slot};
+
+/// Height of a time slot in the SVG, in pixels
+const SVG_ROW_HEIGHT: usize = 14;
+/// Width of a day in the SVG, in pixels
+const SVG_DAY_WIDTH: usize = 220;
+/// Width of the hours column in the SVG, in pixels
+const SVG_HOURS_WIDTH: usize = 50;
+
+const STYLE: &str = "
+* { box-sizing: border-box; }
+body { font-family: sans-serif; margin: 1em; color: #222; }
+h1 { font-size: 1.2em; margin: 0 0 0.5em; }
+.timetable { display: grid; gap: 1px; background: #ccc; border: 1px solid #ccc; }
+.timetable > div { background: #fff; }
+.day { font-weight: bold; text-align: center; padding: 0.3em; }
+.hour { font-size: 0.7em; padding: 0 0.3em; color: #555; }
+.course { font-size: 0.75em; padding: 0.2em 0.4em; overflow: hidden; border-left: 4px solid; }
+.course .name { font-weight: bold; }
+@page { size: A4 landscape; margin: 1cm; }
+@media print {
+  body { margin: 0; }
+  .course { -webkit-print-color-adjust: exact; print-color-adjust: exact; }
+}
+";
+
+const LIST_STYLE: &str = "
+body { font-family: sans-serif; margin: 1em; color: #222; }
+h1 { font-size: 1.2em; margin: 0 0 0.5em; }
+table { border-collapse: collapse; }
+th, td { border: 1px solid #ccc; padding: 0.2em 0.5em; text-align: left; }
+td.course { border-left: 4px solid; }
+";
+
+/// Courses as an HTML page, one row per course
+pub struct Html;
+
+/// Weekly layout shared by the HTML and the SVG
+struct Layout<'a> {
+    /// Start of the rows of the grid
+    slots: &'a [NaiveTime],
+    /// Labels of the displayed rows
+    hours: Vec<String>,
+    /// First displayed row
+    first_row: usize,
+    /// Day's name with its courses split in lanes
+    days: Vec<(&'static str, Vec<Vec<&'a Course>>)>,
+}
+
+impl<'a> Layout<'a> {
+    fn new(timetable: &'a Timetable) -> Self {
+        let slots = &timetable.0;
+        let courses = timetable
+            .1
+             .1
+            .iter()
+            .flat_map(|day| day.courses.iter().flatten())
+            .map(|course| rows(slots, course));
+        let first_row = courses.clone().map(|(first, _)| first).min().unwrap_or(0);
+        let last_row = courses.map(|(_, last)| last).max().unwrap_or(0);
+
+        Self {
+            slots,
+            hours: slots
+                .iter()
+                .take(last_row)
+                .skip(first_row)
+                .map(|slot| hour(*slot))
+                .collect(),
+            first_row,
+            days: timetable
+                .1
+                 .1
+                .iter()
+                .map(|day| {
+                    let mut lanes = assign_lanes(day.courses.iter().flatten());
+                    if lanes.is_empty() {
+                        lanes.push(vec![]);
+                    }
+                    (i18n::weekday(day.weekday), lanes)
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Export the weekly timetable as a standalone page, or as an SVG
+/// image if the file has the `.svg` extension
+pub fn export(timetable: &Timetable, filename: &mut String) -> std::io::Result<()> {
+    *filename = with_extension(filename, "html");
+
+    let svg = std::path::Path::new(filename)
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("svg"));
+
+    let content = if svg {
+        render_svg(timetable)
+    } else {
+        render_html(timetable)
+    };
+
+    file::write(filename, &content)
+}
+
+/// Render the timetable as an HTML page
+pub fn render_html(timetable: &Timetable) -> String {
+    let layout = Layout::new(timetable);
+    let title = Message::WeeklyTitle(timetable.1 .0);
+
+    let lanes = layout
+        .days
+        .iter()
+        .map(|(_, lanes)| lanes.len())
+        .sum::<usize>();
+
+    let mut body = String::new();
+    let _ = writeln!(
+        body,
+        "<div class=\"timetable\" style=\"grid-template-columns: auto repeat({lanes}, 1fr); \
+        grid-template-rows: auto repeat({}, 1.2em);\">",
+        layout.hours.len()
+    );
+
+    // Headers of the days
+    let mut column = 2;
+    for (name, lanes) in &layout.days {
+        let _ = writeln!(
+            body,
+            "<div class=\"day\" style=\"grid-row: 1; grid-column: {column} / span {};\">{}</div>",
+            lanes.len(),
+            escape(name)
+        );
+        column += lanes.len();
+    }
+
+    // Hours, with empty cells to draw the grid
+    for (index, hour) in layout.hours.iter().enumerate() {
+        let _ = writeln!(
+            body,
+            "<div class=\"hour\" style=\"grid-row: {}; grid-column: 1;\">{}</div>",
+            index + 2,
+            escape(hour)
+        );
+        let _ = writeln!(
+            body,
+            "<div style=\"grid-row: {}; grid-column: 2 / span {lanes};\"></div>",
+            index + 2
+        );
+    }
+
+    // Courses
+    let mut column = 2;
+    for (_, lanes) in &layout.days {
+        for lane in lanes {
+            for course in lane {
+                let hue = hue(&course.name);
+                let (first, last) = rows(layout.slots, course);
+                let _ = writeln!(
+                    body,
+                    "<div class=\"course\" style=\"grid-row: {} / span {}; grid-column: {column}; \
+                    background: hsl({hue}, 70%, 90%); border-color: hsl({hue}, 60%, 45%);\">\
+                    <div class=\"name\">{} - {} ({})</div><div>{}</div><div>{}</div><div>{}</div></div>",
+                    first - layout.first_row + 2,
+                    last - first,
+                    escape(&categories(course)),
+                    escape(&course.name),
+                    escape(&course.class),
+                    format_time_slot(course.start, course.end),
+                    escape(&course.room),
+                    escape(course.professor.as_deref().unwrap_or_default()),
+                );
+            }
+            column += 1;
+        }
+    }
+    body.push_str("</div>\n");
+
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"{}\">\n<head>\n<meta charset=\"utf-8\">\n\
+        <title>{title}</title>\n<style>{STYLE}</style>\n</head>\n<body>\n<h1>{title}</h1>\n\
+        {body}</body>\n</html>\n",
+        i18n::lang().tag()
+    )
+}
+
+/// Render the timetable as an SVG image
+pub fn render_svg(timetable: &Timetable) -> String {
+    let layout = Layout::new(timetable);
+
+    let width = SVG_HOURS_WIDTH + SVG_DAY_WIDTH * layout.days.len();
+    let height = SVG_ROW_HEIGHT * (layout.hours.len() + 2);
+
+    let mut body = String::new();
+
+    // Hours
+    for (index, hour) in layout.hours.iter().enumerate() {
+        let y = SVG_ROW_HEIGHT * (index + 2);
+        let _ = writeln!(
+            body,
+            "<line x1=\"0\" y1=\"{y}\" x2=\"{width}\" y2=\"{y}\" stroke=\"#ddd\"/>\
+            <text x=\"4\" y=\"{}\" class=\"hour\">{}</text>",
+            y + SVG_ROW_HEIGHT - 3,
+            escape(hour)
+        );
+    }
+
+    for (index, (name, lanes)) in layout.days.iter().enumerate() {
+        let x = SVG_HOURS_WIDTH + SVG_DAY_WIDTH * index;
+        let _ = writeln!(
+            body,
+            "<line x1=\"{x}\" y1=\"0\" x2=\"{x}\" y2=\"{height}\" stroke=\"#ccc\"/>\
+            <text x=\"{}\" y=\"{}\" class=\"day\">{}</text>",
+            x + SVG_DAY_WIDTH / 2,
+            SVG_ROW_HEIGHT + 3,
+            escape(name)
+        );
+
+        let lane_width = SVG_DAY_WIDTH / lanes.len();
+        for (lane_index, lane) in lanes.iter().enumerate() {
+            let lane_x = x + lane_width * lane_index;
+            for course in lane {
+                let hue = hue(&course.name);
+                let (first, last) = rows(layout.slots, course);
+                let y = SVG_ROW_HEIGHT * (first - layout.first_row + 2);
+                let _ = writeln!(
+                    body,
+                    "<svg x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\">\
+                    <rect width=\"100%\" height=\"100%\" rx=\"3\" \
+                    fill=\"hsl({hue}, 70%, 90%)\" stroke=\"hsl({hue}, 60%, 45%)\"/>\
+                    <text x=\"4\" y=\"12\" class=\"name\">{} - {} ({})</text>\
+                    <text x=\"4\" y=\"24\">{}</text><text x=\"4\" y=\"36\">{}</text>\
+                    <text x=\"4\" y=\"48\">{}</text></svg>",
+                    lane_x + 1,
+                    y + 1,
+                    lane_width - 2,
+                    SVG_ROW_HEIGHT * (last - first) - 2,
+                    escape(&categories(course)),
+                    escape(&course.name),
+                    escape(&course.class),
+                    format_time_slot(course.start, course.end),
+                    escape(&course.room),
+                    escape(course.professor.as_deref().unwrap_or_default()),
+                );
+            }
+        }
+    }
+
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" \
+        font-family=\"sans-serif\" font-size=\"10\">\n\
+        <style>.day {{ font-weight: bold; text-anchor: middle; }} .hour {{ fill: #555; }} \
+        .name {{ font-weight: bold; }}</style>\n\
+        <rect width=\"100%\" height=\"100%\" fill=\"#fff\"/>\n{body}</svg>\n"
+    )
+}
+
+impl Exporter for Html {
+    fn extensions(&self) -> &'static [&'static str] {
+        &["html", "htm"]
+    }
+
+    fn render(&self, mut courses: Vec<Course>, context: &Context) -> String {
+        courses.sort_by_key(|course| course.dtstart);
+
+        let mut body = format!(
+            "<table>\n<tr><th>{}</th><th>{}</th><th>{}</th><th>{}</th><th>{}</th></tr>\n",
+            Message::Date,
+            Message::Schedule,
+            Message::Course,
+            Message::Room,
+            Message::Professor
+        );
+        for course in &courses {
+            let start = course.dtstart.unwrap().naive_utc();
+            let end = course.dtend.unwrap().naive_utc();
+            let hue = hue(&course.name);
+
+            let _ = writeln!(
+                body,
+                "<tr><td>{} {}</td><td>{}-{}</td>\
+                <td class=\"course\" style=\"border-color: hsl({hue}, 60%, 45%);\">{} - {} ({})</td>\
+                <td>{}</td><td>{}</td></tr>",
+                i18n::weekday(start.weekday()),
+                start.format("%d/%m/%Y"),
+                hour(start.time()),
+                hour(end.time()),
+                escape(&categories(course)),
+                escape(&course.name),
+                escape(&course.class),
+                escape(&course.room),
+                escape(course.professor.as_deref().unwrap_or_default()),
+            );
+        }
+        body.push_str("</table>\n");
+
+        let title = escape(&context.metadata.name);
+        format!(
+            "<!DOCTYPE html>\n<html lang=\"{}\">\n<head>\n<meta charset=\"utf-8\">\n\
+            <title>{title}</title>\n<style>{LIST_STYLE}</style>\n</head>\n<body>\n<h1>{title}</h1>\n\
+            {body}</body>\n</html>\n",
+            i18n::lang().tag()
+        )
+    }
+}
+
+/// Categories of the course
+fn categories(course: &Course) -> String {
+    course
+        .category
+        .iter()
+        .map(std::string::ToString::to_string)
+        .collect::<Vec<String>>()
+        .join("/")
+}
+
+/// Stable hue for a subject, so each subject keeps its color
+pub fn hue(name: &str) -> u32 {
+    name.bytes().fold(0_u32, |hash, byte| {
+        hash.wrapping_mul(31).wrapping_add(u32::from(byte))
+    }) % 360
+}
+
+/// Escape the text to put it in HTML or SVG
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Weekday;
+
+    use super::export;
+    use crate::{
+        timetable::models::{course, Category, Day, Timetable},
+        utils::file::temp_dir,
+    };
+
+    fn timetable() -> Timetable {
+        let days = vec![Day {
+            weekday: Weekday::Mon,
+            courses: vec![Some(course("Algo", Category::Cours, (8, 10)))],
+        }];
+
+        (crate::timetable::grid::slots(&days), (1, days))
+    }
+
+    /// Export to the file name, returns the name written and its content
+    fn exported(name: &str) -> (String, String) {
+        let dir = temp_dir(&format!("html-{name}"));
+        let mut filename = dir.join(name).to_string_lossy().into_owned();
+        export(&timetable(), &mut filename).unwrap();
+
+        let content = std::fs::read_to_string(&filename).unwrap();
+        std::fs::remove_dir_all(dir).unwrap();
+        let name = std::path::Path::new(&filename)
+            .file_name()
+            .unwrap()
+            .to_string_lossy()
+            .into_owned();
+        (name, content)
+    }
+
+    #[test]
+    fn pages() {
+        let (name, content) = exported("week");
+        assert_eq!(name, "week.html");
+        assert!(content.starts_with("<!DOCTYPE html>"));
+        assert!(content.contains("Algo"));
+
+        // The extension given is kept
+        let (name, content) = exported("week.htm");
+        assert_eq!(name, "week.htm");
+        assert!(content.starts_with("<!DOCTYPE html>"));
+    }
+
+    #[test]
+    fn images() {
+        let (name, content) = exported("week.svg");
+        assert_eq!(name, "week.svg");
+        assert!(content.starts_with("<svg"));
+        assert!(content.contains("Algo"));
+    }
+}
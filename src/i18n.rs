@@ -0,0 +1,459 @@
+use std::{
+    fmt::{Display, Formatter, Result},
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use chrono::Weekday;
+use clap::ValueEnum;
+
+use crate::timetable::models::Category;
+
+/// Language of the messages
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Lang {
+    /// Français
+    Fr,
+    /// English
+    En,
+}
+
+impl Lang {
+    /// Language tag, i.e.: `fr`
+    pub fn tag(self) -> &'static str {
+        match self {
+            Self::Fr => "fr",
+            Self::En => "en",
+        }
+    }
+}
+
+/// Whether the messages are in English, French by default
+static ENGLISH: AtomicBool = AtomicBool::new(false);
+
+/// Change the language of the messages
+pub fn set(lang: Lang) {
+    ENGLISH.store(lang == Lang::En, Ordering::Relaxed);
+}
+
+/// Current language of the messages
+pub fn lang() -> Lang {
+    if ENGLISH.load(Ordering::Relaxed) {
+        Lang::En
+    } else {
+        Lang::Fr
+    }
+}
+
+/// Language of the locale, following the priority of `setlocale`.
+/// French is kept for the default locales (`C`, `POSIX`)
+pub fn from_env() -> Lang {
+    let locale = ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|value| !value.is_empty())
+        .unwrap_or_default();
+
+    from_locale(&locale)
+}
+
+/// Language of a locale, i.e.: `fr_FR.UTF-8`
+fn from_locale(locale: &str) -> Lang {
+    // Without the encoding and the modifier, i.e.: `C.UTF-8` is `C`
+    let locale = locale.split(['.', '@']).next().unwrap_or_default();
+
+    if locale.is_empty() || locale == "C" || locale == "POSIX" || locale.starts_with("fr") {
+        Lang::Fr
+    } else {
+        Lang::En
+    }
+}
+
+/// Name of the day of the week in the current language
+pub fn weekday(weekday: Weekday) -> &'static str {
+    match (lang(), weekday) {
+        (Lang::Fr, Weekday::Mon) => "Lundi",
+        (Lang::Fr, Weekday::Tue) => "Mardi",
+        (Lang::Fr, Weekday::Wed) => "Mercredi",
+        (Lang::Fr, Weekday::Thu) => "Jeudi",
+        (Lang::Fr, Weekday::Fri) => "Vendredi",
+        (Lang::Fr, Weekday::Sat) => "Samedi",
+        (Lang::Fr, Weekday::Sun) => "Dimanche",
+        (Lang::En, Weekday::Mon) => "Monday",
+        (Lang::En, Weekday::Tue) => "Tuesday",
+        (Lang::En, Weekday::Wed) => "Wednesday",
+        (Lang::En, Weekday::Thu) => "Thursday",
+        (Lang::En, Weekday::Fri) => "Friday",
+        (Lang::En, Weekday::Sat) => "Saturday",
+        (Lang::En, Weekday::Sun) => "Sunday",
+    }
+}
+
+/// Name of the category in the given language
+pub fn category(lang: Lang, category: &Category) -> &'static str {
+    match (lang, category) {
+        (Lang::Fr, Category::Cours) => "Cours",
+        (Lang::Fr, Category::TD) => "TD",
+        (Lang::Fr, Category::TP) => "TP",
+        (Lang::En, Category::Cours) => "Lecture",
+        (Lang::En, Category::TD) => "Tutorial",
+        (Lang::En, Category::TP) => "Lab",
+    }
+}
+
+/// Name of the calendar of the imported events in the given language
+pub fn imported(lang: Lang) -> &'static str {
+    match lang {
+        Lang::Fr => "Importés",
+        Lang::En => "Imported",
+    }
+}
+
+/// Messages showed to the users
+pub enum Message<'a> {
+    // Progress
+    Fetching(i8),
+    FetchingInfo,
+    Displaying,
+    Archiving(i8, i8),
+    Skipped(&'a dyn Display),
+    ArchiveBuilt(&'a dyn Display),
+    PrintableExported(&'a str),
+    SplitExported(&'a str),
+    Exported(&'a str, &'a str),
+    CalDavUpdated(usize, usize, usize, usize),
+
+    // Exports
+    WeeklyTitle(usize),
+    Period(&'a dyn Display, &'a dyn Display),
+    Date,
+    Schedule,
+    Course,
+    Room,
+    Professor,
+
+    // Prompts
+    StartDate(usize),
+    ChooseSubjects,
+    ChooseCourses,
+    ChooseTdTp,
+    ChooseCombination,
+    ChooseManually,
+    Days(usize),
+
+    // Overlaps
+    Overlap(&'a str, &'a dyn Display, &'a dyn Display),
+    OverlapsWith(&'a str),
+    CoursesOverlap,
+    ImportedOverlap,
+    NoCombination,
+    UseSolve,
+
+    // Configuration
+    CommandLine,
+    Environment,
+    Default,
+    InvalidConfig(&'a dyn Display, &'a dyn Display),
+    UnknownOption(&'a str, &'a str),
+
+    // Errors and warnings
+    UnknownCategory(&'a str),
+    DurationMismatch(&'a str, &'a dyn Display, &'a dyn Display),
+    UnknownDuration(&'a str),
+    TimezoneAssumed(&'a str),
+    NoStartDate(usize, &'a dyn Display),
+    InfoUnreachable(&'a dyn Display),
+    NoBackToSchool,
+    NoSemesterStart(i8),
+    NoEquals(&'a str),
+    InvalidValue(&'a dyn Display),
+    WriteFailed(&'a str, &'a dyn Display),
+    CalDavFailed(&'a dyn Display),
+    ImportFailed(&'a dyn Display),
+    ImportUnsupported(&'a str),
+    InvalidLine(&'a str),
+    UnexpectedEnd(&'a str),
+    OutsideComponent(&'a str),
+    MissingEnd(&'a str),
+    StaleCache(&'a dyn Display),
+    ClientFailed(&'a dyn Display),
+    FetchFailed(i8, &'a dyn Display),
+    NoTimetable,
+    InvalidCell(&'a str),
+}
+
+/// Hint of the multiple selections
+const DISCLAIMER_FR: &str = "(selection avec ESPACE, ENTRER pour valider)";
+const DISCLAIMER_EN: &str = "(select with SPACE, ENTER to confirm)";
+
+impl Display for Message<'_> {
+    #[allow(clippy::too_many_lines)]
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        let fr = lang() == Lang::Fr;
+        let disclaimer = if fr { DISCLAIMER_FR } else { DISCLAIMER_EN };
+
+        match *self {
+            Self::Fetching(level) if fr => {
+                write!(f, "Récupération de l'emploi du temps des M{level}...")
+            }
+            Self::Fetching(level) => write!(f, "Fetching the timetable of the M{level}..."),
+            Self::FetchingInfo if fr => {
+                write!(f, "Récupération des informations par rapport à l'année...")
+            }
+            Self::FetchingInfo => write!(f, "Fetching the information about the year..."),
+            Self::Displaying if fr => write!(f, "Affichage..."),
+            Self::Displaying => write!(f, "Displaying..."),
+            Self::Archiving(level, semester) if fr => write!(
+                f,
+                "Archivage de l'emploi du temps des M{level} (semestre {semester})..."
+            ),
+            Self::Archiving(level, semester) => write!(
+                f,
+                "Archiving the timetable of the M{level} (semester {semester})..."
+            ),
+            Self::Skipped(err) if fr => write!(f, "  Ignoré : {err}"),
+            Self::Skipped(err) => write!(f, "  Skipped: {err}"),
+            Self::ArchiveBuilt(directory) if fr => {
+                write!(f, "Archive construite => {directory}")
+            }
+            Self::ArchiveBuilt(directory) => write!(f, "Archive built => {directory}"),
+            Self::PrintableExported(filename) if fr => {
+                write!(f, "Emploi du temps imprimable exporté => {filename}")
+            }
+            Self::PrintableExported(filename) => {
+                write!(f, "Printable timetable exported => {filename}")
+            }
+            Self::SplitExported(directory) if fr => {
+                write!(f, "Fichiers .ICS construits et exportés => {directory}")
+            }
+            Self::SplitExported(directory) => {
+                write!(f, ".ICS files built and exported => {directory}")
+            }
+            Self::Exported(extension, filename) if fr => {
+                write!(f, "Fichier .{extension} construit et exporté => {filename}")
+            }
+            Self::Exported(extension, filename) => {
+                write!(f, ".{extension} file built and exported => {filename}")
+            }
+            Self::CalDavUpdated(created, updated, unchanged, deleted) if fr => write!(
+                f,
+                "Calendrier CalDAV mis à jour => {created} créés, \
+                {updated} mis à jour, {unchanged} inchangés, {deleted} supprimés"
+            ),
+            Self::CalDavUpdated(created, updated, unchanged, deleted) => write!(
+                f,
+                "CalDAV calendar updated => {created} created, \
+                {updated} updated, {unchanged} unchanged, {deleted} deleted"
+            ),
+
+            Self::WeeklyTitle(semester) if fr => {
+                write!(f, "Emploi du temps - Semestre {semester}")
+            }
+            Self::WeeklyTitle(semester) => write!(f, "Timetable - Semester {semester}"),
+            Self::Period(first, last) if fr => write!(f, "Du {first} au {last}"),
+            Self::Period(first, last) => write!(f, "From {first} to {last}"),
+            Self::Date => write!(f, "Date"),
+            Self::Schedule if fr => write!(f, "Horaire"),
+            Self::Schedule => write!(f, "Time"),
+            Self::Course if fr => write!(f, "Cours"),
+            Self::Course => write!(f, "Course"),
+            Self::Room if fr => write!(f, "Salle"),
+            Self::Room => write!(f, "Room"),
+            Self::Professor if fr => write!(f, "Professeur"),
+            Self::Professor => write!(f, "Professor"),
+
+            Self::StartDate(semester) if fr => {
+                write!(f, "Début des cours de la période (semestre {semester})")
+            }
+            Self::StartDate(semester) => {
+                write!(f, "First day of the courses (semester {semester})")
+            }
+            Self::ChooseSubjects if fr => write!(f, "Choisis tes matières {disclaimer}"),
+            Self::ChooseSubjects => write!(f, "Choose your subjects {disclaimer}"),
+            Self::ChooseCourses if fr => {
+                write!(f, "Choisis tes horaires de Cours {disclaimer}")
+            }
+            Self::ChooseCourses => write!(f, "Choose the slots of your lectures {disclaimer}"),
+            Self::ChooseTdTp if fr => write!(f, "Choisis tes horaires de TD/TP {disclaimer}"),
+            Self::ChooseTdTp => write!(f, "Choose the slots of your TD/TP {disclaimer}"),
+            Self::ChooseCombination if fr => write!(f, "Choisis une combinaison de TD/TP"),
+            Self::ChooseCombination => write!(f, "Choose a combination of TD/TP"),
+            Self::ChooseManually if fr => write!(f, "Choisir manuellement"),
+            Self::ChooseManually => write!(f, "Choose manually"),
+            Self::Days(days) if fr => write!(f, "({days} jours)"),
+            Self::Days(days) => write!(f, "({days} days)"),
+
+            Self::Overlap(when, first, second) if fr => {
+                write!(f, "{when} : {first} chevauche {second}")
+            }
+            Self::Overlap(when, first, second) => write!(f, "{when}: {first} overlaps {second}"),
+            Self::OverlapsWith(names) if fr => write!(f, "⚠ chevauche {names}"),
+            Self::OverlapsWith(names) => write!(f, "⚠ overlaps {names}"),
+            Self::CoursesOverlap if fr => write!(f, "Attention, des cours se chevauchent :"),
+            Self::CoursesOverlap => write!(f, "Warning, some courses overlap:"),
+            Self::ImportedOverlap if fr => write!(
+                f,
+                "Attention, des événements importés chevauchent des cours :"
+            ),
+            Self::ImportedOverlap => write!(f, "Warning, some imported events overlap courses:"),
+            Self::NoCombination if fr => write!(
+                f,
+                "Aucune combinaison de TD/TP sans chevauchement n'a été trouvée"
+            ),
+            Self::NoCombination => write!(f, "No combination of TD/TP without overlaps was found"),
+            Self::UseSolve if fr => write!(
+                f,
+                "Utilise --solve pour choisir les TD/TP, ou --allow-conflicts pour continuer"
+            ),
+            Self::UseSolve => write!(
+                f,
+                "Use --solve to choose the TD/TP, or --allow-conflicts to continue"
+            ),
+
+            Self::CommandLine if fr => write!(f, "ligne de commande"),
+            Self::CommandLine => write!(f, "command line"),
+            Self::Environment if fr => write!(f, "environnement"),
+            Self::Environment => write!(f, "environment"),
+            Self::Default if fr => write!(f, "défaut"),
+            Self::Default => write!(f, "default"),
+            Self::InvalidConfig(path, err) if fr => {
+                write!(f, "Configuration invalide dans {path} : {err}")
+            }
+            Self::InvalidConfig(path, err) => write!(f, "Invalid configuration in {path}: {err}"),
+            Self::UnknownOption(origin, key) if fr => {
+                write!(f, "Option inconnue dans {origin} : {key}")
+            }
+            Self::UnknownOption(origin, key) => write!(f, "Unknown option in {origin}: {key}"),
+
+            Self::UnknownCategory(title) if fr => {
+                write!(
+                    f,
+                    "Type de cours inconnu, considéré comme 'COURS' : {title}"
+                )
+            }
+            Self::UnknownCategory(title) => {
+                write!(
+                    f,
+                    "Unknown type of course, falling back to 'COURS': {title}"
+                )
+            }
+            Self::DurationMismatch(title, duration, rows) if fr => write!(
+                f,
+                "Durée de {duration} différente de la case du tableau ({rows}), \
+                la durée est utilisée : {title}"
+            ),
+            Self::DurationMismatch(title, duration, rows) => write!(
+                f,
+                "Duration of {duration} different from the cell of the table ({rows}), \
+                the duration is used: {title}"
+            ),
+            Self::UnknownDuration(title) if fr => write!(
+                f,
+                "Durée illisible, la case du tableau est utilisée : {title}"
+            ),
+            Self::UnknownDuration(title) => write!(
+                f,
+                "Unreadable duration, the cell of the table is used: {title}"
+            ),
+            Self::TimezoneAssumed(tzid) if fr => {
+                write!(f, "Fuseau horaire {tzid} considéré comme Europe/Paris")
+            }
+            Self::TimezoneAssumed(tzid) => write!(f, "Timezone {tzid} treated as Europe/Paris"),
+            Self::NoStartDate(semester, err) if fr => write!(
+                f,
+                "Impossible de trouver le début des cours du semestre {semester}, \
+                utilise --first-day : {err}"
+            ),
+            Self::NoStartDate(semester, err) => write!(
+                f,
+                "Can't find the first day of the semester {semester}, use --first-day: {err}"
+            ),
+            Self::InfoUnreachable(err) if fr => {
+                write!(f, "Impossible de joindre le site des informations : {err}")
+            }
+            Self::InfoUnreachable(err) => write!(f, "Can't reach info website: {err}"),
+            Self::NoBackToSchool if fr => write!(f, "Aucune date de rentrée dans la page"),
+            Self::NoBackToSchool => write!(f, "No back-to-school date in the page"),
+            Self::NoSemesterStart(semester) if fr => write!(
+                f,
+                "Aucune date de début du semestre {semester} dans la page"
+            ),
+            Self::NoSemesterStart(semester) => {
+                write!(f, "No start date of semester {semester} in the page")
+            }
+            Self::NoEquals(arg) if fr => write!(f, "aucun `=` dans `{arg}`"),
+            Self::NoEquals(arg) => write!(f, "no `=` found in `{arg}`"),
+            Self::InvalidValue(err) if fr => write!(f, "valeur invalide : {err}"),
+            Self::InvalidValue(err) => write!(f, "invalid value: {err}"),
+            Self::WriteFailed(filename, err) if fr => {
+                write!(f, "Impossible d'écrire {filename} : {err}")
+            }
+            Self::WriteFailed(filename, err) => write!(f, "Can't write {filename}: {err}"),
+            Self::CalDavFailed(err) if fr => {
+                write!(
+                    f,
+                    "Impossible de mettre à jour le calendrier CalDAV : {err}"
+                )
+            }
+            Self::CalDavFailed(err) => write!(f, "Can't update the CalDAV calendar: {err}"),
+            Self::ImportFailed(err) if fr => {
+                write!(f, "Impossible d'importer le calendrier : {err}")
+            }
+            Self::ImportFailed(err) => write!(f, "Can't import the calendar: {err}"),
+            Self::ImportUnsupported(format) if fr => write!(
+                f,
+                "Les événements importés ne sont exportés qu'en iCalendar, pas en {format}"
+            ),
+            Self::ImportUnsupported(format) => write!(
+                f,
+                "Imported events are only exported to iCalendar, not to {format}"
+            ),
+            Self::InvalidLine(line) if fr => write!(f, "ligne invalide : {line}"),
+            Self::InvalidLine(line) => write!(f, "invalid line: {line}"),
+            Self::UnexpectedEnd(name) if fr => write!(f, "END:{name} inattendu"),
+            Self::UnexpectedEnd(name) => write!(f, "unexpected END:{name}"),
+            Self::OutsideComponent(line) if fr => {
+                write!(f, "propriété hors d'un composant : {line}")
+            }
+            Self::OutsideComponent(line) => write!(f, "property outside of a component: {line}"),
+            Self::MissingEnd(name) if fr => write!(f, "END:{name} manquant"),
+            Self::MissingEnd(name) => write!(f, "missing END:{name}"),
+            Self::StaleCache(err) if fr => write!(
+                f,
+                "Serveur injoignable, la page en cache est utilisée : {err}"
+            ),
+            Self::StaleCache(err) => {
+                write!(f, "Server unreachable, the cached page is used: {err}")
+            }
+            Self::ClientFailed(err) if fr => {
+                write!(f, "Impossible de créer le client HTTP : {err}")
+            }
+            Self::ClientFailed(err) => write!(f, "Can't build the HTTP client: {err}"),
+            Self::FetchFailed(level, err) if fr => write!(
+                f,
+                "Impossible de récupérer l'emploi du temps du M{level} : {err}"
+            ),
+            Self::FetchFailed(level, err) => {
+                write!(f, "Can't get the timetable of M{level}: {err}")
+            }
+            Self::NoTimetable if fr => write!(f, "Aucun emploi du temps dans la page"),
+            Self::NoTimetable => write!(f, "No timetable in the page"),
+            Self::InvalidCell(title) if fr => write!(f, "Cours illisible : {title}"),
+            Self::InvalidCell(title) => write!(f, "Can't read the course: {title}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{from_locale, Lang};
+
+    #[test]
+    fn locales() {
+        for locale in ["", "C", "C.UTF-8", "POSIX", "fr_FR.UTF-8", "fr_BE@euro"] {
+            assert!(from_locale(locale) == Lang::Fr, "{locale}");
+        }
+        for locale in ["en_US.UTF-8", "de_DE", "cy_GB"] {
+            assert!(from_locale(locale) == Lang::En, "{locale}");
+        }
+    }
+}
@@ -1,109 +1,578 @@
-use std::sync::Arc;
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    sync::Arc,
+};
 
-use chrono::TimeZone;
+use chrono::{Datelike, Duration, TimeZone};
+use clap::ValueEnum;
 use ics::{
+    components::{Parameter, Property},
     parameters::{Language, PartStat, Role, TzIDParam, CN},
     properties::{
         Attendee, Categories, Class, Description, DtEnd, DtStart, Location, Summary, Transp,
+        Trigger,
     },
-    Event, ICalendar, Standard,
+    Alarm, Event, ICalendar, Standard,
+};
+use serde::Serialize;
+
+use crate::{
+    export::{Context, Exporter},
+    i18n,
+    timetable::models::{Category, Course},
+    utils::file::write_atomic,
 };
 
-pub fn export(
-    courses: Vec<crate::timetable::models::Course>,
-    filename: &mut String,
+pub mod color;
+pub mod import;
+
+/// Colors given to the calendars, as CSS3 color names
+const PALETTE: [&str; 10] = [
+    "royalblue",
+    "seagreen",
+    "darkorchid",
+    "darkorange",
+    "crimson",
+    "teal",
+    "goldenrod",
+    "slategray",
+    "deeppink",
+    "saddlebrown",
+];
+
+/// Timezone of the courses
+const TIMEZONE: &str = "Europe/Paris";
+
+/// Default template of the events' summary
+pub const DEFAULT_SUMMARY: &str = "{category} - {name}";
+
+/// Default template of the events' description
+pub const DEFAULT_DESCRIPTION: &str = "{group}";
+
+/// Default template of the events' location
+pub const DEFAULT_LOCATION: &str = "{room}";
+
+/// How to split the calendar in multiple files
+#[derive(Clone, Copy, ValueEnum)]
+pub enum Split {
+    /// One calendar per category (Cours, TD, TP)
+    Category,
+    /// One calendar per subject, and per class when several are exported
+    Subject,
+}
+
+/// Calendar listed in the index of a split export
+#[derive(Serialize)]
+struct IndexEntry {
+    name: String,
+    file: String,
+    color: String,
+    events: usize,
+}
+
+/// Properties of the calendar and reminders of its events
+pub struct Metadata {
+    /// Name of the calendar
+    pub name: String,
+    /// Description of the calendar
+    pub description: Option<String>,
+    /// How often the clients should refresh the calendar, in minutes
+    pub refresh_interval: Option<u32>,
+    /// Colors of the categories, overriding the default ones
+    pub colors: Vec<(String, String)>,
+    /// Minutes before the courses of a category or a subject to remind them
+    pub alarms: Vec<(String, u32)>,
+    /// Template of the events' summary
+    pub summary_template: String,
+    /// Template of the events' description
+    pub description_template: String,
+    /// Template of the events' location
+    pub location_template: String,
+    /// Language of the categories in the summary
+    pub lang: i18n::Lang,
+}
+
+/// Position of a course in the calendar
+struct Counters {
+    /// Teaching week of the course in its semester, starting at 1
+    week: usize,
+    /// Number of teaching weeks of the semester
+    weeks: usize,
+    /// Occurrence of the course in its weekly series, starting at 1
+    occurrence: usize,
+    /// Number of occurrences of the course
+    occurrences: usize,
+}
+
+impl Metadata {
+    /// Color of a category, as a CSS3 color name or an hexadecimal value
+    fn color(&self, category: &[Category]) -> String {
+        category
+            .first()
+            .and_then(|category| {
+                self.colors
+                    .iter()
+                    .find(|(name, _)| name.eq_ignore_ascii_case(&category.to_string()))
+            })
+            .map_or_else(
+                || {
+                    match category.first() {
+                        Some(Category::Cours) | None => PALETTE[0],
+                        Some(Category::TD) => PALETTE[1],
+                        Some(Category::TP) => PALETTE[2],
+                    }
+                    .to_owned()
+                },
+                |(_, color)| color.clone(),
+            )
+    }
+
+    /// Minutes before the course to remind it, the subject takes precedence over the category
+    fn alarm(&self, course: &Course) -> Option<u32> {
+        self.alarms
+            .iter()
+            .find(|(target, _)| *target == course.name)
+            .or_else(|| {
+                self.alarms.iter().find(|(target, _)| {
+                    course
+                        .category
+                        .iter()
+                        .any(|category| target.eq_ignore_ascii_case(&category.to_string()))
+                })
+            })
+            .map(|(_, minutes)| *minutes)
+    }
+}
+
+/// iCalendar, with the courses and the imported events in a single calendar
+pub struct Ics;
+
+impl Exporter for Ics {
+    fn extensions(&self) -> &'static [&'static str] {
+        &["ics", "ical", "ifb", "icalendar"]
+    }
+
+    fn render(&self, courses: Vec<Course>, context: &Context) -> String {
+        let counters = counters(&courses);
+        let mut calendar = calendar(
+            courses.into_iter().zip(counters).collect(),
+            context.with_tz,
+            context.metadata,
+            None,
+        );
+        add_imported(&mut calendar, context.imported, context.with_tz);
+
+        calendar.to_string()
+    }
+}
+
+/// Export the courses in a directory, with one calendar per group and an index,
+/// the imported events get their own calendar
+pub fn export_split(
+    courses: Vec<Course>,
+    imported: &import::Imported,
+    directory: &str,
     with_tz: bool,
-) {
+    metadata: &Metadata,
+    split: Split,
+) -> std::io::Result<()> {
+    // Counted before splitting, so each calendar shares the same numbering
+    let counters = counters(&courses);
+
+    // The same subject in several classes is a different course in each
+    let classes = courses
+        .iter()
+        .map(|course| &course.class)
+        .collect::<HashSet<_>>()
+        .len();
+
+    let mut groups: BTreeMap<String, Vec<(Course, Counters)>> = BTreeMap::new();
+    for (course, counter) in courses.into_iter().zip(counters) {
+        let name = match split {
+            Split::Category => categories(&course),
+            Split::Subject if classes > 1 => format!("{} ({})", course.name, course.class),
+            Split::Subject => course.name.clone(),
+        };
+        groups.entry(name).or_default().push((course, counter));
+    }
+
+    let directory = std::path::Path::new(directory);
+    std::fs::create_dir_all(directory)?;
+
+    let mut index = vec![];
+    let mut files = HashSet::new();
+    for (name, courses) in groups {
+        let color = match split {
+            Split::Category => metadata.color(&courses[0].0.category),
+            Split::Subject => PALETTE[crate::html::hue(&name) as usize % PALETTE.len()].to_owned(),
+        };
+        let file = file_name(&name, &mut files);
+
+        index.push(IndexEntry {
+            name: name.clone(),
+            file: file.clone(),
+            color: color.clone(),
+            events: courses.len(),
+        });
+
+        write_atomic(
+            &directory.join(file),
+            &calendar(courses, with_tz, metadata, Some((&name, color))).to_string(),
+        )?;
+    }
+
+    if !imported.events.is_empty() {
+        // In the language of the calendar, like the categories
+        let name = i18n::imported(metadata.lang);
+        let color = PALETTE[PALETTE.len() - 1].to_owned();
+        let file = file_name(name, &mut files);
+
+        index.push(IndexEntry {
+            name: name.to_owned(),
+            file: file.clone(),
+            color: color.clone(),
+            events: imported.events.len(),
+        });
+
+        let mut calendar = calendar(vec![], with_tz, metadata, Some((name, color)));
+        add_imported(&mut calendar, imported, with_tz);
+        write_atomic(&directory.join(file), &calendar.to_string())?;
+    }
+
+    write_atomic(
+        &directory.join("index.json"),
+        &serde_json::to_string_pretty(&index).unwrap(),
+    )
+}
+
+/// Name of the file of a calendar, suffixed when another calendar already took it
+fn file_name(name: &str, taken: &mut HashSet<String>) -> String {
+    let stem = name.replace(|c: char| !c.is_alphanumeric() && c != '-', "_");
+
+    let mut file = format!("{stem}.ics");
+    // Compared without the case, for the case-insensitive file systems
+    for n in 2.. {
+        if taken.insert(file.to_lowercase()) {
+            break;
+        }
+        file = format!("{stem}-{n}.ics");
+    }
+
+    file
+}
+
+/// Number the teaching weeks of each semester and the occurrences of each course
+fn counters(courses: &[Course]) -> Vec<Counters> {
+    let start = |course: &Course| course.dtstart.unwrap();
+    let monday = |course: &Course| {
+        let date = start(course).date_naive();
+        (
+            course.semester,
+            date - Duration::days(date.weekday().num_days_from_monday().into()),
+        )
+    };
+    let series = |course: &Course| {
+        (
+            course.semester,
+            course.class.clone(),
+            course.name.clone(),
+            categories(course),
+            start(course).weekday(),
+            start(course).time(),
+        )
+    };
+
+    let mut weeks: HashMap<_, Vec<_>> = HashMap::new();
+    for week in courses.iter().map(monday).collect::<BTreeSet<_>>() {
+        weeks.entry(week.0).or_default().push(week.1);
+    }
+
+    let mut occurrences: HashMap<_, Vec<_>> = HashMap::new();
+    for course in courses {
+        occurrences
+            .entry(series(course))
+            .or_default()
+            .push(start(course));
+    }
+    for dates in occurrences.values_mut() {
+        dates.sort();
+    }
+
+    courses
+        .iter()
+        .map(|course| {
+            let dates = &occurrences[&series(course)];
+            let (semester, monday) = monday(course);
+            let weeks = &weeks[&semester];
+            Counters {
+                week: weeks.binary_search(&monday).unwrap() + 1,
+                weeks: weeks.len(),
+                occurrence: dates.partition_point(|date| *date < start(course)) + 1,
+                occurrences: dates.len(),
+            }
+        })
+        .collect()
+}
+
+/// Fill the placeholders of the template in a single pass, so a value is never
+/// read as a placeholder, the unknown placeholders are kept as is
+fn render(template: &str, course: &Course, categories: &str, counters: &Counters) -> String {
+    let value = |key: &str| -> Option<String> {
+        Some(match key {
+            "category" => categories.to_owned(),
+            "name" => course.name.clone(),
+            "room" => course.room.clone(),
+            "professor" => course.professor.clone().unwrap_or_default(),
+            "group" => course.data.clone().unwrap_or_default(),
+            "class" => course.class.clone(),
+            "week" => counters.week.to_string(),
+            "weeks" => counters.weeks.to_string(),
+            "occurrence" => counters.occurrence.to_string(),
+            "occurrences" => counters.occurrences.to_string(),
+            _ => return None,
+        })
+    };
+
+    let mut text = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        text.push_str(&rest[..open]);
+        rest = &rest[open..];
+
+        let replaced = rest
+            .find('}')
+            .and_then(|close| Some((value(&rest[1..close])?, close)));
+        if let Some((value, close)) = replaced {
+            text.push_str(&value);
+            rest = &rest[close + 1..];
+        } else {
+            text.push('{');
+            rest = &rest[1..];
+        }
+    }
+    text.push_str(rest);
+
+    text
+}
+
+/// Categories of the course
+pub fn categories(course: &Course) -> String {
+    course
+        .category
+        .iter()
+        .map(std::string::ToString::to_string)
+        .collect::<Arc<[String]>>()
+        .join("/")
+}
+
+/// Build the calendar, `group` is the name and the color of
+/// the part of the calendar when it's split
+fn calendar(
+    courses: Vec<(Course, Counters)>,
+    with_tz: bool,
+    metadata: &Metadata,
+    group: Option<(&str, String)>,
+) -> ICalendar<'static> {
     let mut calendar = ICalendar::new("2.0", "cal7tor");
 
+    // Name and description of the calendar
+    let name = match &group {
+        Some((group, _)) => format!("{} - {group}", metadata.name),
+        None => metadata.name.clone(),
+    };
+    calendar.push(Property::new("NAME", name.clone()));
+    calendar.push(Property::new("X-WR-CALNAME", name));
+    if let Some(description) = &metadata.description {
+        calendar.push(Property::new("DESCRIPTION", description.clone()));
+        calendar.push(Property::new("X-WR-CALDESC", description.clone()));
+    }
+
+    // Ask the clients to check for updates
+    if let Some(minutes) = metadata.refresh_interval {
+        let mut refresh = Property::new("REFRESH-INTERVAL", format!("PT{minutes}M"));
+        refresh.add(Parameter::new("VALUE", "DURATION"));
+        calendar.push(refresh);
+        calendar.push(Property::new("X-PUBLISHED-TTL", format!("PT{minutes}M")));
+    }
+
+    // Color of the split calendar
+    if let Some((_, color)) = group {
+        if let Some(hex) = color::hex(&color) {
+            calendar.push(Property::new("X-APPLE-CALENDAR-COLOR", hex));
+        }
+        if let Some(name) = color::name(&color) {
+            calendar.push(Property::new("COLOR", name));
+        }
+    }
+
     // Add Europe/Paris timezone
-    let timezone_name = "Europe/Paris";
     if with_tz {
-        calendar.add_timezone(ics::TimeZone::standard(
-            timezone_name,
-            Standard::new(
-                // Add a Z because it's UTC
-                dt_ical(chrono::Utc.with_ymd_and_hms(1970, 1, 1, 0, 0, 0).unwrap()) + "Z",
-                "+0100",
-                "+0200",
-            ),
-        ));
+        calendar.add_timezone(timezone());
     }
 
     // Create events which contains the information regarding the course
-    for course in courses {
-        let mut event = Event::new(
-            uuid::Uuid::new_v4().to_string(),
+    for (course, counters) in courses {
+        calendar.add_event(event(course, &counters, with_tz, metadata));
+    }
+
+    calendar
+}
+
+/// Europe/Paris timezone
+fn timezone() -> ics::TimeZone<'static> {
+    ics::TimeZone::standard(
+        TIMEZONE,
+        Standard::new(
             // Add a Z because it's UTC
-            dt_ical(chrono::Utc::now()) + "Z",
-        );
+            dt_ical(chrono::Utc.with_ymd_and_hms(1970, 1, 1, 0, 0, 0).unwrap()) + "Z",
+            "+0100",
+            "+0200",
+        ),
+    )
+}
 
-        // Public event
-        event.push(Class::public());
+/// Unique identifier of the course, the same course keeps it between exports
+fn uid(course: &Course) -> String {
+    // Parallel groups only differ by their room or their group
+    let key = format!(
+        "{}|{}|{}|{}|{}|{}",
+        course.class,
+        course.name,
+        categories(course),
+        dt_ical(course.dtstart.unwrap()),
+        course.room,
+        course.data.as_deref().unwrap_or_default()
+    );
 
-        // Consume actual time
-        event.push(Transp::opaque());
+    uuid::Uuid::new_v5(&uuid::Uuid::NAMESPACE_OID, key.as_bytes()).to_string()
+}
 
-        // Professor's name
-        if course.professor.is_some() {
-            let name = course.professor.unwrap();
-            let mut contact = Attendee::new("mailto:place@holder.com");
-            contact.add(CN::new(name));
-            contact.add(PartStat::ACCEPTED);
-            contact.add(Role::CHAIR);
-            event.push(contact);
-        }
+/// Build the event of a course
+fn event(
+    course: Course,
+    counters: &Counters,
+    with_tz: bool,
+    metadata: &Metadata,
+) -> Event<'static> {
+    let categories = categories(&course);
+    let alarm = metadata.alarm(&course);
 
-        // Start time of the course
-        let mut date_start = DtStart::new(dt_ical(course.dtstart.unwrap()));
-        if with_tz {
-            date_start.add(TzIDParam::new(timezone_name));
-        }
-        event.push(date_start);
+    // The labels of the categories are in the language of the calendar
+    let labels = course
+        .category
+        .iter()
+        .map(|category| i18n::category(metadata.lang, category))
+        .collect::<Vec<_>>()
+        .join("/");
+    let summary = render(&metadata.summary_template, &course, &labels, counters);
+    let description = render(&metadata.description_template, &course, &labels, counters);
+    let location = render(&metadata.location_template, &course, &labels, counters);
 
-        // End time of the course
-        let mut date_end = DtEnd::new(dt_ical(course.dtend.unwrap()));
-        if with_tz {
-            date_end.add(TzIDParam::new(timezone_name));
-        }
-        event.push(date_end);
+    let mut event = Event::new(
+        uid(&course),
+        // Add a Z because it's UTC
+        dt_ical(chrono::Utc::now()) + "Z",
+    );
 
-        // Room location
-        event.push(Location::new(course.room));
+    // Public event
+    event.push(Class::public());
 
-        let categories = course
-            .category
-            .iter()
-            .map(std::string::ToString::to_string)
-            .collect::<Arc<[String]>>()
-            .join("/");
+    // Consume actual time
+    event.push(Transp::opaque());
+
+    // Professor's name
+    if let Some(name) = course.professor {
+        let mut contact = Attendee::new("mailto:place@holder.com");
+        contact.add(CN::new(name));
+        contact.add(PartStat::ACCEPTED);
+        contact.add(Role::CHAIR);
+        event.push(contact);
+    }
+
+    // Start time of the course
+    let mut date_start = DtStart::new(dt_ical(course.dtstart.unwrap()));
+    if with_tz {
+        date_start.add(TzIDParam::new(TIMEZONE));
+    }
+    event.push(date_start);
 
-        // Course's name
-        let mut course_name = Summary::new(format!("{} - {}", categories, course.name));
-        course_name.add(Language::new("fr"));
-        event.push(course_name);
+    // End time of the course
+    let mut date_end = DtEnd::new(dt_ical(course.dtend.unwrap()));
+    if with_tz {
+        date_end.add(TzIDParam::new(TIMEZONE));
+    }
+    event.push(date_end);
 
-        // Course's category
-        event.push(Categories::new(categories));
+    // Room location
+    event.push(Location::new(location));
 
-        // Course extra data
-        if course.data.is_some() {
-            event.push(Description::new(course.data.unwrap()));
-        }
+    // Course's name
+    let mut course_name = Summary::new(summary.clone());
+    course_name.add(Language::new(metadata.lang.tag()));
+    event.push(course_name);
 
-        // Add the course to the calendar
-        calendar.add_event(event);
+    // Course's category and class
+    event.push(Categories::new(format!("{categories},{}", course.class)));
+
+    // Color of the category
+    if let Some(name) = color::name(&metadata.color(&course.category)) {
+        event.push(Property::new("COLOR", name));
     }
 
-    // Add the extension if needed
-    if !std::path::Path::new(filename)
-        .extension()
-        .map_or(false, |ext| ext.eq_ignore_ascii_case("ics"))
-    {
-        *filename = format!("{filename}.ics");
-    };
+    // Reminder before the course
+    if let Some(minutes) = alarm {
+        event.add_alarm(Alarm::display(
+            Trigger::new(format!("-PT{minutes}M")),
+            Description::new(summary),
+        ));
+    }
+
+    // Course extra data, as written on the website
+    if !description.trim().is_empty() {
+        event.push(Description::new(description));
+    }
 
-    calendar.save_file(filename).unwrap();
+    event
+}
+
+/// Calendars holding a single course each, as needed by `CalDAV`, with their UID
+pub fn resources(
+    courses: Vec<Course>,
+    with_tz: bool,
+    metadata: &Metadata,
+) -> Vec<(String, String)> {
+    let counters = counters(&courses);
+
+    courses
+        .into_iter()
+        .zip(counters)
+        .map(|(course, counters)| {
+            let uid = uid(&course);
+
+            let mut calendar = ICalendar::new("2.0", "cal7tor");
+            if with_tz {
+                calendar.add_timezone(timezone());
+            }
+            calendar.add_event(event(course, &counters, with_tz, metadata));
+
+            (uid, calendar.to_string())
+        })
+        .collect()
+}
+
+/// Add the imported events to the calendar, with the timezones they use
+fn add_imported(calendar: &mut ICalendar<'static>, imported: &import::Imported, with_tz: bool) {
+    for timezone in &imported.timezones {
+        // Europe/Paris is already defined
+        if with_tz && timezone.value("TZID") == Some(TIMEZONE) {
+            continue;
+        }
+        calendar.add_component(timezone.to_component());
+    }
+
+    for event in &imported.events {
+        calendar.add_component(event.to_component());
+    }
 }
 
 /// Transform the datetime from chrono to the ICS format
@@ -111,3 +580,213 @@ pub fn export(
 fn dt_ical(dt: chrono::DateTime<chrono::Utc>) -> String {
     format!("{}", dt.format("%Y%m%dT%H%M%S"))
 }
+
+/// Metadata of a calendar without options, for the tests
+#[cfg(test)]
+pub(crate) fn metadata() -> Metadata {
+    Metadata {
+        name: "M1".to_owned(),
+        description: None,
+        refresh_interval: None,
+        colors: vec![],
+        alarms: vec![],
+        summary_template: "{name}".to_owned(),
+        description_template: "{group}".to_owned(),
+        location_template: "{room}".to_owned(),
+        lang: i18n::Lang::Fr,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use serde_json::Value;
+
+    use super::{
+        counters, export_split, import::Imported, metadata, render, resources, Counters, Split,
+    };
+    use crate::{
+        timetable::models::{self, Category, Course},
+        utils::file::temp_dir,
+    };
+
+    /// Course of the given semester, on the day at 8h
+    fn course(name: &str, semester: usize, (year, month, day): (i32, u32, u32)) -> Course {
+        let start = Utc.with_ymd_and_hms(year, month, day, 8, 0, 0).unwrap();
+        Course {
+            professor: Some("Ada".to_owned()),
+            room: "2001".to_owned(),
+            dtstart: Some(start),
+            dtend: Some(start + chrono::Duration::hours(2)),
+            data: Some("Groupe 1".to_owned()),
+            semester,
+            ..models::course(name, Category::TD, (8, 10))
+        }
+    }
+
+    fn numbers(counters: &Counters) -> (usize, usize, usize, usize) {
+        (
+            counters.week,
+            counters.weeks,
+            counters.occurrence,
+            counters.occurrences,
+        )
+    }
+
+    #[test]
+    fn templates() {
+        let course = course("Algo", 1, (2026, 9, 14));
+        let counters = Counters {
+            week: 2,
+            weeks: 12,
+            occurrence: 1,
+            occurrences: 10,
+        };
+
+        assert_eq!(
+            render(
+                "[{category}] {name} - {room}, {professor} ({class})",
+                &course,
+                "TD",
+                &counters
+            ),
+            "[TD] Algo - 2001, Ada (M1)"
+        );
+        assert_eq!(
+            render(
+                "{group}: {week}/{weeks} {occurrence}/{occurrences}",
+                &course,
+                "TD",
+                &counters
+            ),
+            "Groupe 1: 2/12 1/10"
+        );
+        // Unknown and unclosed placeholders are kept
+        assert_eq!(
+            render("{unknown} {{name}} {name", &course, "TD", &counters),
+            "{unknown} {Algo} {name"
+        );
+    }
+
+    #[test]
+    fn values_are_not_placeholders() {
+        let mut course = course("{room}", 1, (2026, 9, 14));
+        course.room = "{name}".to_owned();
+        let counters = Counters {
+            week: 1,
+            weeks: 1,
+            occurrence: 1,
+            occurrences: 1,
+        };
+
+        assert_eq!(
+            render("{name} {room}", &course, "TD", &counters),
+            "{room} {name}"
+        );
+    }
+
+    #[test]
+    fn weeks_and_occurrences() {
+        let courses = [
+            course("Algo", 1, (2026, 9, 14)),
+            course("Réseau", 1, (2026, 9, 16)),
+            course("Algo", 1, (2026, 9, 21)),
+            // No course in the week of the 28th
+            course("Algo", 1, (2026, 10, 5)),
+        ];
+
+        let counters = counters(&courses).iter().map(numbers).collect::<Vec<_>>();
+        assert_eq!(
+            counters,
+            [(1, 3, 1, 3), (1, 3, 1, 1), (2, 3, 2, 3), (3, 3, 3, 3)]
+        );
+    }
+
+    #[test]
+    fn weeks_restart_each_semester() {
+        let courses = [
+            course("Algo", 1, (2026, 9, 14)),
+            course("Algo", 1, (2026, 9, 21)),
+            course("Algo", 2, (2027, 1, 18)),
+            course("Algo", 2, (2027, 1, 25)),
+            course("Algo", 2, (2027, 2, 1)),
+        ];
+
+        let counters = counters(&courses).iter().map(numbers).collect::<Vec<_>>();
+        assert_eq!(
+            counters,
+            [
+                (1, 2, 1, 2),
+                (2, 2, 2, 2),
+                (1, 3, 1, 3),
+                (2, 3, 2, 3),
+                (3, 3, 3, 3)
+            ]
+        );
+    }
+
+    #[test]
+    fn split_files_are_distinct() {
+        let mut other = course("Algo", 1, (2026, 9, 15));
+        other.class = "M2".to_owned();
+        let courses = vec![
+            course("Algo/I", 1, (2026, 9, 14)),
+            course("Algo I", 1, (2026, 9, 14)),
+            course("Algo", 1, (2026, 9, 14)),
+            other,
+        ];
+        let directory = temp_dir("split");
+
+        export_split(
+            courses,
+            &Imported::default(),
+            directory.to_str().unwrap(),
+            false,
+            &metadata(),
+            Split::Subject,
+        )
+        .unwrap();
+
+        let index = std::fs::read_to_string(directory.join("index.json")).unwrap();
+        let index = serde_json::from_str::<Value>(&index).unwrap();
+        let calendars = index
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|entry| {
+                (
+                    entry["name"].as_str().unwrap(),
+                    entry["file"].as_str().unwrap(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            calendars,
+            [
+                ("Algo (M1)", "Algo__M1_.ics"),
+                ("Algo (M2)", "Algo__M2_.ics"),
+                ("Algo I (M1)", "Algo_I__M1_.ics"),
+                ("Algo/I (M1)", "Algo_I__M1_-2.ics"),
+            ]
+        );
+        for (_, file) in calendars {
+            assert!(directory.join(file).exists());
+        }
+
+        let _ = std::fs::remove_dir_all(directory);
+    }
+
+    #[test]
+    fn parallel_groups_are_distinct() {
+        let first = course("Algo", 1, (2026, 9, 14));
+        let second = Course {
+            room: "2002".to_owned(),
+            data: Some("Groupe 2".to_owned()),
+            ..first.clone()
+        };
+
+        let resources = resources(vec![first, second], false, &metadata());
+        assert_ne!(resources[0].0, resources[1].0);
+    }
+}
@@ -0,0 +1,561 @@
+use std::{collections::BTreeSet, path::Path, sync::Mutex};
+
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, Weekday};
+use ics::components::{Component, Parameter, Property};
+
+use crate::i18n::Message;
+
+/// Maximum number of occurrences computed for a recurring event
+const MAX_OCCURRENCES: usize = 1000;
+
+/// Timezones already reported as treated as Europe/Paris
+static ASSUMED_TIMEZONES: Mutex<BTreeSet<String>> = Mutex::new(BTreeSet::new());
+
+/// Content line of a calendar
+pub struct Line {
+    pub name: String,
+    pub parameters: Vec<(String, String)>,
+    pub value: String,
+}
+
+/// Component of a calendar, i.e.: an event with its alarms
+pub struct Node {
+    pub name: String,
+    pub lines: Vec<Line>,
+    pub children: Vec<Node>,
+}
+
+/// Components read from existing calendars
+#[derive(Default)]
+pub struct Imported {
+    /// Events of the calendars
+    pub events: Vec<Node>,
+    /// Timezones used by the events
+    pub timezones: Vec<Node>,
+}
+
+/// Occurrence of an imported event, in local time
+pub struct Occurrence {
+    pub summary: String,
+    pub start: NaiveDateTime,
+    pub end: NaiveDateTime,
+}
+
+/// Read the events and the timezones of the calendars
+pub fn read(paths: &[impl AsRef<Path>]) -> Result<Imported, String> {
+    let mut imported = Imported::default();
+
+    for path in paths {
+        let path = path.as_ref();
+        let content =
+            std::fs::read_to_string(path).map_err(|err| format!("{}: {err}", path.display()))?;
+
+        for calendar in parse(&content).map_err(|err| format!("{}: {err}", path.display()))? {
+            for node in calendar.children {
+                match node.name.as_str() {
+                    "VEVENT" => imported.events.push(node),
+                    // Keep only one definition per timezone
+                    "VTIMEZONE"
+                        if !imported
+                            .timezones
+                            .iter()
+                            .any(|tz| tz.value("TZID") == node.value("TZID")) =>
+                    {
+                        imported.timezones.push(node);
+                    }
+                    _ => (),
+                }
+            }
+        }
+    }
+
+    Ok(imported)
+}
+
+/// Parse the content of a calendar into its components
+fn parse(content: &str) -> Result<Vec<Node>, String> {
+    // Unfold the long lines
+    let mut lines: Vec<String> = vec![];
+    for line in content.lines() {
+        match line.strip_prefix([' ', '\t']) {
+            Some(rest) if !lines.is_empty() => lines.last_mut().unwrap().push_str(rest),
+            _ if line.is_empty() => (),
+            _ => lines.push(line.to_owned()),
+        }
+    }
+
+    let mut roots = vec![];
+    let mut stack: Vec<Node> = vec![];
+    for raw in lines {
+        let line = parse_line(&raw).ok_or_else(|| Message::InvalidLine(&raw).to_string())?;
+        match line.name.as_str() {
+            "BEGIN" => stack.push(Node {
+                name: line.value.to_uppercase(),
+                lines: vec![],
+                children: vec![],
+            }),
+            "END" => {
+                let node = stack
+                    .pop()
+                    .filter(|node| node.name.eq_ignore_ascii_case(&line.value))
+                    .ok_or_else(|| Message::UnexpectedEnd(&line.value).to_string())?;
+                match stack.last_mut() {
+                    Some(parent) => parent.children.push(node),
+                    None => roots.push(node),
+                }
+            }
+            _ => stack
+                .last_mut()
+                .ok_or_else(|| Message::OutsideComponent(&raw).to_string())?
+                .lines
+                .push(line),
+        }
+    }
+
+    if let Some(node) = stack.last() {
+        return Err(Message::MissingEnd(&node.name).to_string());
+    }
+
+    Ok(roots)
+}
+
+/// Parse a content line, i.e.: `DTSTART;TZID=Europe/Paris:20260914T083000`
+fn parse_line(raw: &str) -> Option<Line> {
+    // Separators inside quoted parameters values don't count
+    let mut quoted = false;
+    let mut parts = vec![];
+    let mut last = 0;
+    let mut value = None;
+    for (index, c) in raw.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            ';' if !quoted => {
+                parts.push(&raw[last..index]);
+                last = index + 1;
+            }
+            ':' if !quoted => {
+                parts.push(&raw[last..index]);
+                value = Some(&raw[index + 1..]);
+                break;
+            }
+            _ => (),
+        }
+    }
+
+    let (name, parameters) = parts.split_first()?;
+    Some(Line {
+        name: name.to_uppercase(),
+        parameters: parameters
+            .iter()
+            .filter_map(|parameter| parameter.split_once('='))
+            .map(|(key, value)| (key.to_uppercase(), value.to_owned()))
+            .collect(),
+        value: value?.to_owned(),
+    })
+}
+
+impl Line {
+    /// Value of the parameter with the given name
+    pub fn parameter(&self, name: &str) -> Option<&str> {
+        self.parameters
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+impl Node {
+    /// First line with the given name
+    pub fn line(&self, name: &str) -> Option<&Line> {
+        self.lines.iter().find(|line| line.name == name)
+    }
+
+    /// Value of the first line with the given name
+    pub fn value(&self, name: &str) -> Option<&str> {
+        self.line(name).map(|line| line.value.as_str())
+    }
+
+    /// Turn the node back to a component of a calendar
+    pub fn to_component(&self) -> Component<'static> {
+        let mut component = Component::new(self.name.clone());
+        for line in &self.lines {
+            let mut property = Property::new(line.name.clone(), line.value.clone());
+            for (key, value) in &line.parameters {
+                property.add(Parameter::new(key.clone(), value.clone()));
+            }
+            component.add_property(property);
+        }
+        for child in &self.children {
+            component.add_component(child.to_component());
+        }
+
+        component
+    }
+
+    /// Occurrences of the event until the given date
+    pub fn occurrences(&self, until: NaiveDateTime) -> Vec<Occurrence> {
+        let Some(start) = self.line("DTSTART").and_then(datetime) else {
+            return vec![];
+        };
+        let length = self
+            .line("DTEND")
+            .and_then(datetime)
+            .map(|end| end - start)
+            .or_else(|| self.value("DURATION").and_then(duration))
+            .unwrap_or_else(|| {
+                // All-day event when the start is a date
+                if self
+                    .value("DTSTART")
+                    .is_some_and(|value| !value.contains('T'))
+                {
+                    Duration::days(1)
+                } else {
+                    Duration::zero()
+                }
+            });
+
+        // Dates removed from the recurrence, a date without time removes the whole day
+        let excluded = self
+            .lines
+            .iter()
+            .filter(|line| line.name == "EXDATE")
+            .flat_map(|line| {
+                let tzid = line.parameter("TZID");
+                line.value.split(',').filter_map(move |value| {
+                    Some((parse_datetime(value, tzid)?, !value.contains('T')))
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let summary = self.value("SUMMARY").unwrap_or_default().to_owned();
+        let starts = self
+            .value("RRULE")
+            .map_or_else(|| vec![start], |rule| recurrences(start, rule, until));
+
+        starts
+            .into_iter()
+            .filter(|start| {
+                !excluded.iter().any(|(date, whole_day)| {
+                    if *whole_day {
+                        date.date() == start.date()
+                    } else {
+                        date == start
+                    }
+                })
+            })
+            .map(|start| Occurrence {
+                summary: summary.clone(),
+                start,
+                end: start + length,
+            })
+            .collect()
+    }
+}
+
+/// Starts of a recurring event, only daily and weekly rules are supported
+fn recurrences(start: NaiveDateTime, rule: &str, until: NaiveDateTime) -> Vec<NaiveDateTime> {
+    let parts = rule
+        .split(';')
+        .filter_map(|part| part.split_once('='))
+        .collect::<Vec<_>>();
+    let part = |name: &str| {
+        parts
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| *value)
+    };
+
+    let step = match part("FREQ") {
+        Some("DAILY") => Duration::days(1),
+        Some("WEEKLY") => Duration::weeks(1),
+        _ => return vec![start],
+    };
+    // At least one period, or the loop would never end
+    let interval = part("INTERVAL")
+        .and_then(|value| value.parse::<i32>().ok())
+        .unwrap_or(1)
+        .max(1);
+    let count = part("COUNT")
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(MAX_OCCURRENCES)
+        .min(MAX_OCCURRENCES);
+    // A date without time includes the whole day
+    let until = part("UNTIL")
+        .and_then(|value| {
+            let end = parse_datetime(value, None)?;
+            Some(if value.contains('T') {
+                end
+            } else {
+                end + Duration::days(1) - Duration::seconds(1)
+            })
+        })
+        .map_or(until, |end| end.min(until));
+
+    // Days of the week, only used by weekly rules
+    let days = match part("BYDAY") {
+        Some(days) if step == Duration::weeks(1) => days
+            .split(',')
+            .filter_map(
+                |day| match day.trim_start_matches(|c: char| !c.is_alphabetic()) {
+                    "MO" => Some(Weekday::Mon),
+                    "TU" => Some(Weekday::Tue),
+                    "WE" => Some(Weekday::Wed),
+                    "TH" => Some(Weekday::Thu),
+                    "FR" => Some(Weekday::Fri),
+                    "SA" => Some(Weekday::Sat),
+                    "SU" => Some(Weekday::Sun),
+                    _ => None,
+                },
+            )
+            .collect::<Vec<_>>(),
+        _ => vec![],
+    };
+
+    let mut starts = vec![];
+    let mut period = start;
+    while starts.len() < count && period <= until {
+        if days.is_empty() {
+            starts.push(period);
+        } else {
+            // Each day of the week of this period
+            let monday = period - Duration::days(period.weekday().num_days_from_monday().into());
+            let mut week = days
+                .iter()
+                .map(|day| monday + Duration::days(day.num_days_from_monday().into()))
+                .filter(|date| *date >= start && *date <= until)
+                .collect::<Vec<_>>();
+            week.sort();
+            starts.extend(week.into_iter().take(count - starts.len()));
+        }
+        period += step * interval;
+    }
+
+    starts
+}
+
+/// Local datetime of a line, handling UTC and dates without time
+fn datetime(line: &Line) -> Option<NaiveDateTime> {
+    parse_datetime(&line.value, line.parameter("TZID"))
+}
+
+/// Parse a date or a datetime, UTC times are converted to the time in Paris
+fn parse_datetime(value: &str, tzid: Option<&str>) -> Option<NaiveDateTime> {
+    if let Some(utc) = value.strip_suffix('Z') {
+        let datetime = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok()?;
+        return Some(datetime + paris_offset(datetime));
+    }
+
+    // Warn only once per timezone, the events usually share it
+    if let Some(tzid) = tzid.filter(|tzid| *tzid != super::TIMEZONE) {
+        if ASSUMED_TIMEZONES.lock().unwrap().insert(tzid.to_owned()) {
+            eprintln!("{}", Message::TimezoneAssumed(tzid));
+        }
+    }
+
+    NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S")
+        .ok()
+        .or_else(|| {
+            NaiveDate::parse_from_str(value, "%Y%m%d")
+                .ok()
+                .map(|date| date.and_time(NaiveTime::MIN))
+        })
+}
+
+/// Offset of Paris from UTC, summer time is from the last sunday
+/// of march to the last sunday of october, at 01:00 UTC
+fn paris_offset(utc: NaiveDateTime) -> Duration {
+    let last_sunday = |month: u32| {
+        let last_day =
+            NaiveDate::from_ymd_opt(utc.year(), month + 1, 1).unwrap() - Duration::days(1);
+        last_day - Duration::days(last_day.weekday().num_days_from_sunday().into())
+    };
+    let one = NaiveTime::from_hms_opt(1, 0, 0).unwrap();
+
+    if utc >= last_sunday(3).and_time(one) && utc < last_sunday(10).and_time(one) {
+        Duration::hours(2)
+    } else {
+        Duration::hours(1)
+    }
+}
+
+/// Parse a duration, i.e.: `PT1H30M`
+fn duration(value: &str) -> Option<Duration> {
+    let value = value.strip_prefix('P')?;
+    let (date, time) = value.split_once('T').unwrap_or((value, ""));
+
+    let mut total = Duration::zero();
+    for (part, units) in [
+        (date, &[('W', 7 * 24 * 3600), ('D', 24 * 3600)][..]),
+        (time, &[('H', 3600), ('M', 60), ('S', 1)][..]),
+    ] {
+        let mut number = String::new();
+        for c in part.chars() {
+            if c.is_ascii_digit() {
+                number.push(c);
+            } else {
+                let seconds = units.iter().find(|(unit, _)| *unit == c)?.1;
+                total += Duration::seconds(number.parse::<i64>().ok()? * seconds);
+                number.clear();
+            }
+        }
+    }
+
+    Some(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, NaiveDate, NaiveDateTime};
+
+    use super::{duration, paris_offset, parse, recurrences};
+
+    fn at(day: (i32, u32, u32), hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(day.0, day.1, day.2)
+            .unwrap()
+            .and_hms_opt(hour, minute, 0)
+            .unwrap()
+    }
+
+    fn event(lines: &str) -> super::Node {
+        let content =
+            format!("BEGIN:VCALENDAR\nBEGIN:VEVENT\n{lines}\nEND:VEVENT\nEND:VCALENDAR\n");
+        parse(&content)
+            .unwrap()
+            .pop()
+            .unwrap()
+            .children
+            .pop()
+            .unwrap()
+    }
+
+    #[test]
+    fn components() {
+        let calendars = parse(
+            "BEGIN:VCALENDAR\r\n\
+             BEGIN:VEVENT\r\n\
+             SUMMARY:Réunion\r\n  du labo\r\n\
+             DTSTART;TZID=\"Europe/Paris\";VALUE=DATE-TIME:20260914T083000\r\n\
+             BEGIN:VALARM\r\n\
+             TRIGGER:-PT15M\r\n\
+             END:VALARM\r\n\
+             END:VEVENT\r\n\
+             END:VCALENDAR\r\n",
+        )
+        .unwrap();
+
+        assert_eq!(calendars.len(), 1);
+        let event = &calendars[0].children[0];
+        assert_eq!(event.name, "VEVENT");
+        // Folded lines are joined
+        assert_eq!(event.value("SUMMARY"), Some("Réunion du labo"));
+        // Separators inside quotes don't split the line
+        let start = event.line("DTSTART").unwrap();
+        assert_eq!(start.parameter("TZID"), Some("\"Europe/Paris\""));
+        assert_eq!(start.parameter("VALUE"), Some("DATE-TIME"));
+        assert_eq!(start.value, "20260914T083000");
+        assert_eq!(event.children[0].value("TRIGGER"), Some("-PT15M"));
+    }
+
+    #[test]
+    fn invalid_calendars() {
+        let error = |content| parse(content).err().unwrap();
+
+        assert!(error("BEGIN:VCALENDAR\nBEGIN:VEVENT\nEND:VCALENDAR\n").contains("END:VCALENDAR"));
+        assert!(error("BEGIN:VCALENDAR\n").contains("END:VCALENDAR"));
+        assert!(error("SUMMARY:outside\n").contains("SUMMARY:outside"));
+        assert!(error("BEGIN:VCALENDAR\nnot a line\nEND:VCALENDAR\n").contains("not a line"));
+    }
+
+    #[test]
+    fn durations() {
+        assert_eq!(
+            duration("PT1H30M"),
+            Some(Duration::hours(1) + Duration::minutes(30))
+        );
+        assert_eq!(duration("P1D"), Some(Duration::days(1)));
+        assert_eq!(duration("P2W"), Some(Duration::weeks(2)));
+        assert_eq!(
+            duration("P1DT2H5S"),
+            Some(Duration::days(1) + Duration::hours(2) + Duration::seconds(5))
+        );
+        assert_eq!(duration("1H"), None);
+        assert_eq!(duration("PT1X"), None);
+    }
+
+    #[test]
+    fn summer_time() {
+        // In 2026, from March 29th to October 25th at 01:00 UTC
+        assert_eq!(paris_offset(at((2026, 1, 15), 12, 0)), Duration::hours(1));
+        assert_eq!(paris_offset(at((2026, 3, 29), 0, 59)), Duration::hours(1));
+        assert_eq!(paris_offset(at((2026, 3, 29), 1, 0)), Duration::hours(2));
+        assert_eq!(paris_offset(at((2026, 7, 14), 12, 0)), Duration::hours(2));
+        assert_eq!(paris_offset(at((2026, 10, 25), 0, 59)), Duration::hours(2));
+        assert_eq!(paris_offset(at((2026, 10, 25), 1, 0)), Duration::hours(1));
+        assert_eq!(paris_offset(at((2026, 12, 31), 23, 0)), Duration::hours(1));
+    }
+
+    #[test]
+    fn weekly_recurrences() {
+        let start = at((2026, 9, 14), 10, 0);
+        let far = at((2027, 1, 1), 0, 0);
+
+        assert_eq!(
+            recurrences(start, "FREQ=WEEKLY;COUNT=3", far),
+            [start, at((2026, 9, 21), 10, 0), at((2026, 9, 28), 10, 0)]
+        );
+        assert_eq!(
+            recurrences(start, "FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,TH;COUNT=3", far),
+            [start, at((2026, 9, 17), 10, 0), at((2026, 9, 28), 10, 0)]
+        );
+        // The search stops at the given date
+        assert_eq!(
+            recurrences(start, "FREQ=DAILY", at((2026, 9, 16), 12, 0)).len(),
+            3
+        );
+        // Unsupported rules only give the first occurrence
+        assert_eq!(recurrences(start, "FREQ=MONTHLY", far), [start]);
+        // Invalid intervals are taken as one period
+        for interval in ["0", "-2"] {
+            let rule = format!("FREQ=DAILY;INTERVAL={interval}");
+            assert_eq!(recurrences(start, &rule, at((2026, 9, 16), 12, 0)).len(), 3);
+        }
+    }
+
+    #[test]
+    fn until_a_date_includes_the_day() {
+        let start = at((2026, 9, 14), 10, 0);
+        let far = at((2027, 1, 1), 0, 0);
+
+        assert_eq!(
+            recurrences(start, "FREQ=WEEKLY;UNTIL=20260928", far).last(),
+            Some(&at((2026, 9, 28), 10, 0))
+        );
+        assert_eq!(
+            recurrences(start, "FREQ=WEEKLY;UNTIL=20260928T000000", far).last(),
+            Some(&at((2026, 9, 21), 10, 0))
+        );
+    }
+
+    #[test]
+    fn excluded_dates() {
+        let far = at((2027, 1, 1), 0, 0);
+
+        let event = event(
+            "SUMMARY:Sport\n\
+             DTSTART:20260914T100000\n\
+             DTEND:20260914T110000\n\
+             RRULE:FREQ=WEEKLY;COUNT=5\n\
+             EXDATE:20260921T100000,20261005T100000\n\
+             EXDATE;VALUE=DATE:20260928",
+        );
+        let starts = event
+            .occurrences(far)
+            .iter()
+            .map(|occurrence| occurrence.start)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            starts,
+            [at((2026, 9, 14), 10, 0), at((2026, 10, 12), 10, 0)]
+        );
+    }
+}
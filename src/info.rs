@@ -3,24 +3,29 @@ use regex::{Captures, Regex};
 use scraper::Selector;
 use std::{collections::HashMap, sync::Arc};
 
-use crate::utils::{
-    get_period_weeks, get_semester, get_webpage, get_year,
-    models::{Info, InfoList, InfoType},
+use crate::{
+    i18n::Message,
+    utils::{
+        fetcher::Fetcher,
+        get_period_weeks, get_semester, get_year,
+        models::{Info, InfoList, InfoType},
+    },
 };
 
 pub async fn get_start_date(
     level: i8,
     semester_opt: Option<i8>,
     year_opt: Option<i32>,
-    user_agent: &str,
-) -> String {
+    fetcher: &Fetcher,
+) -> Result<String, String> {
     let semester = get_semester(semester_opt);
     let year = get_year(year_opt, semester);
 
     // Fetch the timetable of the FIRST semester
-    let document = get_webpage(level, 1, &year, user_agent)
+    let document = fetcher
+        .get_webpage(level, 1, &year)
         .await
-        .expect("Can't reach info website.");
+        .map_err(|err| Message::InfoUnreachable(&err).to_string())?;
 
     // Selectors
     let sel_b = Selector::parse("b").unwrap();
@@ -30,27 +35,20 @@ pub async fn get_start_date(
     let raw_data = document
         .select(&sel_b)
         .find(|element| element.select(&sel_font).next().is_some())
-        .unwrap()
+        .ok_or_else(|| Message::NoBackToSchool.to_string())?
         .inner_html();
 
-    let first_semester = semester == 1;
-
-    let re = Regex::new(if first_semester {
+    let re = Regex::new(if semester == 1 {
         r"\d{1,2} (septembre|octobre)"
     } else {
         r"\d{1,2} (janvier|février)"
     })
     .unwrap();
 
-    re.captures(&raw_data).and_then(|caps| caps.get(0)).map_or(
-        if first_semester {
-            "1 septembre"
-        } else {
-            "1 janvier"
-        }
-        .to_owned(),
-        |m| m.as_str().to_owned(),
-    )
+    re.captures(&raw_data)
+        .and_then(|caps| caps.get(0))
+        .map(|m| m.as_str().to_owned())
+        .ok_or_else(|| Message::NoSemesterStart(semester).to_string())
 }
 
 #[allow(clippy::bool_to_int_with_if)]
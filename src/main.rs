@@ -1,33 +1,112 @@
-use clap::Parser;
+use std::{io::IsTerminal, path::PathBuf, time::Duration};
+
+use clap::{Parser, Subcommand, ValueEnum};
 use dialoguer::Input;
+use i18n::Message;
 use regex::Regex;
+use utils::fetcher::Fetcher;
 
+mod archive;
+mod caldav;
+mod config;
+mod conflicts;
+mod export;
 mod filter;
+mod html;
+mod i18n;
 mod ics;
 mod info;
+mod solver;
 mod timetable;
 mod utils;
 
 #[allow(clippy::struct_excessive_bools)]
 #[derive(Parser)]
-#[clap(version, about, long_about = None)]
+#[clap(version, about, long_about = None, subcommand_negates_reqs = true)]
 struct Args {
-    /// The class you want to get the timetable, i.e.: M1
-    #[clap(value_parser)]
-    class: String,
+    #[clap(subcommand)]
+    command: Option<Command>,
 
-    /// The semester you want (1 or 2), default to current semester
-    #[clap(short, long, value_parser, value_name = "SEMESTER NUMBER")]
-    semester: Option<i8>,
+    /// The classes you want to get the timetable, i.e.: M1, or M1 M2 to merge them
+    #[clap(value_parser, required = true)]
+    class: Vec<String>,
 
-    /// The year, default to current year
-    #[clap(short, long, value_parser, value_name = "YEAR")]
+    /// The semester you want (1, 2 or all), default to current semester
+    #[clap(short, long, value_enum, value_name = "SEMESTER NUMBER")]
+    semester: Option<SemesterChoice>,
+
+    /// The year, default to current year, the one the school year starts with
+    /// `--semester all` and `archive`
+    #[clap(short, long, value_parser, value_name = "YEAR", global = true)]
     year: Option<i32>,
 
-    /// Export to iCalendar format (.ics)
+    /// Export the calendar, `-` for the standard output
     #[clap(short, long, value_name = "FILE NAME")]
     export: Option<String>,
 
+    /// Format of the export, default to the one of the file extension, or iCalendar
+    #[clap(long, value_enum, requires = "export")]
+    format: Option<export::Format>,
+
+    /// Split the exported calendar, the file name is then used as a directory
+    #[clap(long, value_enum, requires = "export")]
+    split: Option<ics::Split>,
+
+    /// Name of the exported calendar, default to the classes, semesters and year
+    #[clap(long, value_name = "NAME")]
+    calendar_name: Option<String>,
+
+    /// Description of the exported calendar
+    #[clap(long, value_name = "DESCRIPTION")]
+    calendar_description: Option<String>,
+
+    /// How often calendar clients should check for updates
+    #[clap(long, value_name = "MINUTES")]
+    refresh_interval: Option<u32>,
+
+    /// Color of a category in the exported calendar, as a CSS3 color name or
+    /// an hexadecimal value, i.e.: TP=crimson
+    #[clap(long, value_parser = key_value::<String>, value_name = "CATEGORY=COLOR")]
+    color: Vec<(String, String)>,
+
+    /// Reminder before the courses of a category or a subject, i.e.: TP=15
+    #[clap(long, value_parser = key_value::<u32>, value_name = "CATEGORY|SUBJECT=MINUTES")]
+    alarm: Vec<(String, u32)>,
+
+    /// Template of the events' summary, placeholders are {category}, {name}, {room},
+    /// {professor}, {group}, {class}, {week}, {weeks}, {occurrence} and {occurrences}
+    #[clap(long, value_name = "TEMPLATE", default_value = ics::DEFAULT_SUMMARY)]
+    summary_template: String,
+
+    /// Template of the events' description, with the same placeholders as the summary
+    #[clap(long, value_name = "TEMPLATE", default_value = ics::DEFAULT_DESCRIPTION)]
+    description_template: String,
+
+    /// Template of the events' location, with the same placeholders as the summary
+    #[clap(long, value_name = "TEMPLATE", default_value = ics::DEFAULT_LOCATION)]
+    location_template: String,
+
+    /// Language of the categories in the events' summary, apart from the messages
+    #[clap(long, value_enum, value_name = "LANG", default_value = "fr")]
+    ics_lang: i18n::Lang,
+
+    /// Upload the courses to a calendar server (URL of the collection)
+    #[clap(long, value_name = "URL")]
+    caldav: Option<String>,
+
+    /// User of the calendar server, the password is read from the environment
+    #[clap(long, value_name = "USER", requires = "caldav")]
+    caldav_user: Option<String>,
+
+    /// Merge the events of an existing calendar (.ics) into the export, only
+    /// supported by the iCalendar format
+    #[clap(long, value_name = "FILE", requires = "export")]
+    import: Vec<PathBuf>,
+
+    /// Export a printable weekly timetable, as HTML or SVG (.svg)
+    #[clap(long, value_name = "FILE NAME")]
+    printable: Option<String>,
+
     /// Doesn't distinguish TD from TP
     #[clap(short, long)]
     td_are_tp: bool,
@@ -36,9 +115,9 @@ struct Args {
     #[clap(short, long, value_parser)]
     weeks: Option<i32>,
 
-    /// First day of your year
+    /// First day of your year, repeat it for each semester with `--semester all`
     #[clap(long)]
-    first_day: Option<String>,
+    first_day: Vec<String>,
 
     /// If TD/TP start a week after courses
     #[clap(long)]
@@ -51,59 +130,515 @@ struct Args {
     /// Guess the holidays and apply it to the generated calendar
     #[clap(long)]
     holidays: bool,
+
+    /// Never ask anything, keep every course or the best combination found with --solve.
+    /// Implied when there is no terminal
+    #[clap(long)]
+    non_interactive: bool,
+
+    /// Export even when some courses overlap and no one can fix the selection
+    #[clap(long)]
+    allow_conflicts: bool,
+
+    /// Propose the best combinations of TD/TP without overlaps
+    #[clap(long)]
+    solve: bool,
+
+    /// Preferences used by the solver
+    #[clap(
+        long,
+        value_enum,
+        value_delimiter = ',',
+        value_name = "PREFERENCES",
+        requires = "solve"
+    )]
+    prefer: Vec<solver::Preference>,
+
+    /// Ignore the cached pages and fetch them again
+    #[clap(long, global = true)]
+    refresh: bool,
+
+    /// Timeout of the requests
+    #[clap(long, value_name = "SECONDS", default_value_t = 5, global = true)]
+    timeout: u64,
+
+    /// Number of retries when the website can't be reached
+    #[clap(long, default_value_t = 3, global = true)]
+    retries: u32,
+
+    /// Proxy used for the requests
+    #[clap(long, value_name = "URL", global = true)]
+    proxy: Option<String>,
+
+    /// Base URL of the timetables, to use a mirror
+    #[clap(long, value_name = "URL", global = true)]
+    base_url: Option<String>,
+
+    /// Read the timetables from an archive instead of the website
+    #[clap(long, value_name = "DIRECTORY")]
+    offline: Option<PathBuf>,
+
+    /// Language of the messages, default to the one of the locale
+    #[clap(long, value_enum, global = true)]
+    lang: Option<i18n::Lang>,
+
+    /// Size of cell of the timetable (irrelevant when exporting the timetable)
+    #[clap(short, long, value_name = "CELL LENGTH", default_value_t = 35)]
+    cl: usize,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum SemesterChoice {
+    /// First semester
+    #[value(name = "1")]
+    First,
+    /// Second semester
+    #[value(name = "2")]
+    Second,
+    /// Both semesters of the school year
+    All,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Archive the timetables of every class and semester of the year
+    Archive {
+        /// Directory where the archives are stored
+        #[clap(short, long, value_name = "DIRECTORY", default_value = "archive")]
+        output: PathBuf,
+    },
+    /// Manage the configuration, read from the user's config directory, then
+    /// from ./cal7tor.toml, then from the environment variables
+    Config {
+        #[clap(subcommand)]
+        action: ConfigAction,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigAction {
+    /// Print the effective configuration, with the origin of each value
+    Show,
 }
 
 #[tokio::main]
 async fn main() {
-    let args = Args::parse();
-
-    let matches = Regex::new(r"(?i)M(?P<level>[1,2])")
-        .unwrap()
-        .captures(&args.class)
-        .unwrap();
+    let (args, effective) = parse();
 
-    let level = matches
-        .name("level")
-        .unwrap()
-        .as_str()
-        .parse::<i8>()
-        .unwrap();
+    if let Some(Command::Config { action }) = &args.command {
+        match action {
+            ConfigAction::Show => effective.show(),
+        }
+        return;
+    }
 
+    // Only built for the commands which fetch
     let user_agent = format!("cal7tor/{}", env!("CARGO_PKG_VERSION"));
+    let fetcher = Fetcher::new(
+        &user_agent,
+        Duration::from_secs(args.timeout),
+        args.proxy.as_deref(),
+        args.base_url.as_deref(),
+        args.retries,
+        args.refresh,
+        args.offline.clone(),
+    )
+    .unwrap_or_else(|err| fail(&Message::ClientFailed(&err)));
+
+    if let Some(Command::Archive { output }) = &args.command {
+        archive::archive(&fetcher, args.year, output).await;
+        return;
+    }
+
+    let levels = levels(&args.class);
+    let level = levels[0];
+
+    let semesters = semesters(args.semester, args.year);
+
+    // The prompts need a terminal
+    let interactive =
+        !args.non_interactive && std::io::stdin().is_terminal() && std::io::stderr().is_terminal();
+
+    let mut builded_timetable = vec![];
+    for (index, (semester, year)) in semesters.iter().copied().enumerate() {
+        let timetable = filter::timetable(
+            fetch(&levels, semester, year, &fetcher).await,
+            args.td_are_tp,
+            args.solve.then_some(&args.prefer[..]),
+            interactive,
+        );
+
+        // Without anyone to fix the selection, overlapping courses is an error,
+        // unless another slot of one of them is kept
+        let conflicts = conflicts::timetable(&timetable);
+        conflicts::report(&conflicts);
+        if !interactive
+            && !args.allow_conflicts
+            && conflicts
+                .iter()
+                .any(|conflict| conflicts::unavoidable(&timetable, conflict))
+        {
+            fail(&Message::UseSolve);
+        }
+
+        if let Some(filename) = &args.printable {
+            // One file per semester when there are many
+            let mut filename = if semesters.len() > 1 {
+                with_semester(filename, timetable.1 .0)
+            } else {
+                filename.clone()
+            };
+
+            // Export the weekly timetable
+            let result = html::export(&timetable, &mut filename);
+            check_written(result, &filename);
+
+            if filename != "-" {
+                eprintln!("{}", Message::PrintableExported(&filename));
+            }
+        }
+
+        if args.export.is_none() && args.caldav.is_none() {
+            if args.printable.is_none() {
+                // Show the calendar
+                println!("{}", Message::Displaying);
+                timetable::display(&timetable, args.cl);
+            }
+            continue;
+        }
+
+        let date = match args.first_day.get(index) {
+            Some(day) => day.clone(),
+            None => {
+                first_day(
+                    level,
+                    (semester, year),
+                    timetable.1 .0,
+                    interactive,
+                    &fetcher,
+                )
+                .await
+            }
+        };
+
+        eprintln!("{}", Message::FetchingInfo);
+        let info = info::info(
+            semester,
+            year,
+            &date,
+            args.week_skip,
+            args.holidays,
+            args.weeks,
+        );
+
+        builded_timetable.extend(timetable::build(&timetable, &info));
+    }
+
+    let imported = import(&args.import, &builded_timetable);
+    let metadata = metadata(&args, &levels, &semesters);
+
+    if let Some(url) = &args.caldav {
+        let resources = ics::resources(builded_timetable.clone(), !args.no_tz, &metadata);
+        upload(&fetcher, url, args.caldav_user.as_deref(), resources).await;
+    }
+
+    if let Some(filename) = &args.export {
+        export(&args, filename, builded_timetable, &imported, &metadata);
+    }
+}
+
+/// Parse the arguments, the messages are in the language of the locale until then
+fn parse() -> (Args, config::Effective) {
+    i18n::set(i18n::from_env());
+    let (args, effective) = config::parse::<Args>();
+    if let Some(lang) = args.lang {
+        i18n::set(lang);
+    }
+
+    // Only the calendars can hold the imported events
+    if let Some(filename) = &args.export {
+        let format = format(&args, filename);
+        if !args.import.is_empty() && args.split.is_none() && !matches!(format, export::Format::Ics)
+        {
+            fail(&Message::ImportUnsupported(
+                format.exporter().extensions()[0],
+            ));
+        }
+    }
+
+    (args, effective)
+}
+
+/// First day of the semester, detected from the website and confirmed by the user
+async fn first_day(
+    level: i8,
+    (semester, year): (Option<i8>, Option<i32>),
+    number: usize,
+    interactive: bool,
+    fetcher: &Fetcher,
+) -> String {
+    let detected = info::get_start_date(level, semester, year, fetcher).await;
+
+    if !interactive {
+        return detected.unwrap_or_else(|err| fail(&Message::NoStartDate(number, &err)));
+    }
+
+    let mut input = Input::new().with_prompt(Message::StartDate(number).to_string());
+    if let Ok(date) = detected {
+        input = input.default(date);
+    }
+
+    input.interact_text().unwrap()
+}
 
-    println!("Récupération de l'emploi du temps des M{level}...");
-    let mut timetable = timetable::timetable(level, args.semester, args.year, &user_agent).await;
+/// Levels of the classes, i.e.: 1 for M1
+fn levels(classes: &[String]) -> Vec<i8> {
+    let re = Regex::new(r"(?i)M(?P<level>[1,2])").unwrap();
+    let mut levels = classes
+        .iter()
+        .map(|class| {
+            re.captures(class)
+                .unwrap()
+                .name("level")
+                .unwrap()
+                .as_str()
+                .parse::<i8>()
+                .unwrap()
+        })
+        .collect::<Vec<_>>();
+    levels.sort_unstable();
+    levels.dedup();
 
-    timetable = filter::timetable(timetable, args.td_are_tp);
+    levels
+}
+
+/// Semesters with the year to use for each of them
+fn semesters(choice: Option<SemesterChoice>, year: Option<i32>) -> Vec<(Option<i8>, Option<i32>)> {
+    match choice {
+        None => vec![(None, year)],
+        Some(SemesterChoice::First) => vec![(Some(1), year)],
+        Some(SemesterChoice::Second) => vec![(Some(2), year)],
+        Some(SemesterChoice::All) => {
+            // Both semesters must be from the same school year, the asked year
+            // being the one it starts
+            let start = utils::get_school_year(year)
+                .split_once('-')
+                .unwrap()
+                .0
+                .parse::<i32>()
+                .unwrap();
+            vec![(Some(1), Some(start)), (Some(2), Some(start + 1))]
+        }
+    }
+}
 
-    let date = match args.first_day {
-        None => Input::new()
-            .with_prompt("Début des cours de la période")
-            .default(info::get_start_date(level, args.semester, args.year, &user_agent).await)
-            .interact_text()
-            .unwrap(),
-        Some(day) => day,
+/// Add the semester to the file name, before the extension
+fn with_semester(filename: &str, semester: usize) -> String {
+    let path = std::path::Path::new(filename);
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let name = match path.extension() {
+        Some(ext) => format!("{stem}-S{semester}.{}", ext.to_string_lossy()),
+        None => format!("{stem}-S{semester}"),
     };
 
-    println!("Récupération des informations par rapport à l'année...");
-    let info = info::info(
-        args.semester,
-        args.year,
-        &date,
-        args.week_skip,
-        args.holidays,
-        args.weeks,
-    );
-
-    if let Some(mut filename) = args.export {
-        // Export the calendar
-        let builded_timetable = timetable::build(&timetable, &info);
-        ics::export(builded_timetable, &mut filename, !args.no_tz);
-
-        println!("Fichier .ICS construit et exporté => {filename}");
+    path.with_file_name(name).to_string_lossy().into_owned()
+}
+
+/// Export the calendar to the file, or to the directory when it's split
+fn export(
+    args: &Args,
+    filename: &str,
+    courses: Vec<timetable::models::Course>,
+    imported: &ics::import::Imported,
+    metadata: &ics::Metadata,
+) {
+    // Export the calendar
+    if let Some(split) = args.split {
+        let result = ics::export_split(courses, imported, filename, !args.no_tz, metadata, split);
+        check_written(result, filename);
+
+        eprintln!("{}", Message::SplitExported(filename));
     } else {
-        // Show the calendar
-        println!("Affichage...");
-        timetable::display(&timetable);
+        let exporter = format(args, filename).exporter();
+
+        // A file without extension gets the usual one of the format
+        let filename = if args.format.is_none() {
+            export::with_extension(filename, exporter.extensions()[0])
+        } else {
+            filename.to_owned()
+        };
+
+        let context = export::Context {
+            metadata,
+            with_tz: !args.no_tz,
+            imported,
+        };
+        let result = export::export(exporter.as_ref(), courses, &filename, &context);
+        check_written(result, &filename);
+
+        if filename != "-" {
+            let extension = exporter.extensions()[0].to_uppercase();
+            eprintln!("{}", Message::Exported(&extension, &filename));
+        }
+    }
+}
+
+/// Format of the export, from the file name when not given
+fn format(args: &Args, filename: &str) -> export::Format {
+    args.format
+        .or_else(|| export::Format::from_filename(filename))
+        .unwrap_or(export::Format::Ics)
+}
+
+/// Fetch the timetables of the classes, merged together
+async fn fetch(
+    levels: &[i8],
+    semester: Option<i8>,
+    year: Option<i32>,
+    fetcher: &Fetcher,
+) -> timetable::models::Timetable {
+    let mut timetables = vec![];
+    for &level in levels {
+        eprintln!("{}", Message::Fetching(level));
+        timetables.push(
+            timetable::timetable(level, semester, year, fetcher)
+                .await
+                .unwrap_or_else(|err| fail(&Message::FetchFailed(level, &err))),
+        );
+    }
+    timetable::merge(timetables)
+}
+
+/// Stop when the export couldn't be written
+fn check_written(result: std::io::Result<()>, filename: &str) {
+    if let Err(err) = result {
+        fail(&Message::WriteFailed(filename, &err));
+    }
+}
+
+/// Show the error and stop
+fn fail(message: &Message) -> ! {
+    eprintln!("{message}");
+    std::process::exit(1);
+}
+
+/// Upload the courses to the `CalDAV` collection
+async fn upload(
+    fetcher: &Fetcher,
+    url: &str,
+    user: Option<&str>,
+    resources: Vec<(String, String)>,
+) {
+    let credentials = user.map(|user| caldav::Credentials {
+        user: user.to_owned(),
+        password: std::env::var("CAL7TOR_CALDAV_PASSWORD").ok(),
+    });
+
+    // Kept even after a failure, for the resources uploaded before it
+    let mut state = caldav::State::load(fetcher.cache(), url);
+    let result = caldav::upload(fetcher, url, credentials.as_ref(), resources, &mut state).await;
+    state.store(fetcher.cache(), url);
+
+    match result {
+        Ok(report) => eprintln!(
+            "{}",
+            Message::CalDavUpdated(
+                report.created,
+                report.updated,
+                report.unchanged,
+                report.deleted
+            )
+        ),
+        Err(err) => fail(&Message::CalDavFailed(&err)),
+    }
+}
+
+/// Properties of the exported calendar
+fn metadata(args: &Args, levels: &[i8], semesters: &[(Option<i8>, Option<i32>)]) -> ics::Metadata {
+    ics::Metadata {
+        name: args
+            .calendar_name
+            .clone()
+            .unwrap_or_else(|| calendar_name(levels, semesters)),
+        description: args.calendar_description.clone(),
+        refresh_interval: args.refresh_interval,
+        colors: args.color.clone(),
+        alarms: args.alarm.clone(),
+        summary_template: args.summary_template.clone(),
+        description_template: args.description_template.clone(),
+        location_template: args.location_template.clone(),
+        lang: args.ics_lang,
+    }
+}
+
+/// Default name of the calendar, i.e.: M1 S1 2026-2027
+fn calendar_name(levels: &[i8], semesters: &[(Option<i8>, Option<i32>)]) -> String {
+    let (semester, year) = semesters[0];
+
+    format!(
+        "{} {} {}",
+        levels
+            .iter()
+            .map(|level| format!("M{level}"))
+            .collect::<Vec<_>>()
+            .join("-"),
+        semesters
+            .iter()
+            .map(|(semester, _)| format!("S{}", utils::get_semester(*semester)))
+            .collect::<Vec<_>>()
+            .join("-"),
+        utils::get_year(year, utils::get_semester(semester))
+    )
+}
+
+/// Read the calendars to merge and report their events overlapping the courses
+fn import(paths: &[PathBuf], courses: &[timetable::models::Course]) -> ics::import::Imported {
+    let imported =
+        ics::import::read(paths).unwrap_or_else(|err| fail(&Message::ImportFailed(&err)));
+
+    // Check the imported events against the courses
+    if let Some(until) = courses.iter().filter_map(|course| course.dtend).max() {
+        let occurrences = imported
+            .events
+            .iter()
+            .flat_map(|event| event.occurrences(until.naive_utc()))
+            .collect::<Vec<_>>();
+        conflicts::report_imported(&conflicts::imported(courses, &occurrences));
+    }
+
+    imported
+}
+
+/// Parse a `KEY=VALUE` argument
+fn key_value<T: std::str::FromStr>(arg: &str) -> Result<(String, T), String>
+where
+    T::Err: std::fmt::Display,
+{
+    let (key, value) = arg
+        .split_once('=')
+        .ok_or_else(|| Message::NoEquals(arg).to_string())?;
+
+    Ok((
+        key.to_owned(),
+        value
+            .parse()
+            .map_err(|err| Message::InvalidValue(&err).to_string())?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{semesters, SemesterChoice};
+
+    #[test]
+    fn whole_school_year() {
+        assert_eq!(
+            semesters(Some(SemesterChoice::All), Some(2026)),
+            [(Some(1), Some(2026)), (Some(2), Some(2027))]
+        );
+        assert_eq!(
+            semesters(Some(SemesterChoice::Second), Some(2027)),
+            [(Some(2), Some(2027))]
+        );
     }
 }
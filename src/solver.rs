@@ -0,0 +1,282 @@
+use std::collections::HashSet;
+
+use chrono::{NaiveTime, Weekday};
+use clap::ValueEnum;
+
+use crate::{conflicts::overlapping, timetable::models::Course};
+
+/// Number of combinations kept by the solver
+const PROPOSALS: usize = 5;
+
+/// Start of the early courses
+const EARLY: NaiveTime = NaiveTime::from_hms_opt(8, 0, 0).unwrap();
+
+/// When the afternoon starts
+const AFTERNOON: NaiveTime = NaiveTime::from_hms_opt(12, 0, 0).unwrap();
+
+/// What the user wants to avoid in its timetable
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Preference {
+    /// No course starting at 8h00
+    NoEarly,
+    /// Wednesday afternoon without courses
+    FreeWednesday,
+    /// As few days on campus as possible
+    FewestDays,
+}
+
+/// A combination of slots without any overlap
+pub struct Solution {
+    /// Index of the slot picked in each group
+    pub picks: Vec<usize>,
+    /// Penalty given by the preferences, lower is better
+    pub score: usize,
+    /// Number of days with at least one course
+    pub days: usize,
+}
+
+/// A course with the name of its day
+type Slot<'a> = (&'a Course, Weekday);
+
+struct Search<'a> {
+    /// Slots to choose from, one per group
+    groups: &'a [Vec<Slot<'a>>],
+    /// User preferences
+    preferences: &'a [Preference],
+    /// Best solutions found so far, sorted
+    best: Vec<Solution>,
+}
+
+/// Find the best combinations of slots, picking exactly one slot per group
+pub fn solve<'a>(
+    fixed: &'a [Slot<'a>],
+    groups: &'a [Vec<Slot<'a>>],
+    preferences: &'a [Preference],
+) -> Vec<Solution> {
+    let mut search = Search {
+        groups,
+        preferences,
+        best: vec![],
+    };
+
+    let mut chosen = fixed.iter().collect::<Vec<_>>();
+    search.explore(&mut vec![], &mut chosen);
+
+    search.best
+}
+
+impl<'a> Search<'a> {
+    /// Try every slot of the next group compatible with the courses already chosen
+    fn explore(&mut self, picks: &mut Vec<usize>, chosen: &mut Vec<&'a Slot<'a>>) {
+        // Adding courses never lowers the score, stop when it can't beat the worst kept
+        let rank = self.rank(chosen);
+        if self.best.len() == PROPOSALS
+            && self
+                .best
+                .last()
+                .is_some_and(|worst| rank >= (worst.score, worst.days))
+        {
+            return;
+        }
+
+        let Some(group) = self.groups.get(picks.len()) else {
+            let position = self
+                .best
+                .partition_point(|solution| (solution.score, solution.days) <= rank);
+            self.best.insert(
+                position,
+                Solution {
+                    picks: picks.clone(),
+                    score: rank.0,
+                    days: rank.1,
+                },
+            );
+            self.best.truncate(PROPOSALS);
+            return;
+        };
+
+        for (index, slot) in group.iter().enumerate() {
+            let same_day = chosen
+                .iter()
+                .filter(|(_, day)| *day == slot.1)
+                .map(|(course, _)| *course);
+            if !overlapping(slot.0, same_day).is_empty() {
+                continue;
+            }
+
+            picks.push(index);
+            chosen.push(slot);
+            self.explore(picks, chosen);
+            chosen.pop();
+            picks.pop();
+        }
+    }
+
+    /// Score and number of days of a set of courses
+    fn rank(&self, chosen: &[&Slot]) -> (usize, usize) {
+        let days = chosen
+            .iter()
+            .map(|(_, day)| *day)
+            .collect::<HashSet<_>>()
+            .len();
+
+        let score = self
+            .preferences
+            .iter()
+            .map(|preference| match preference {
+                Preference::NoEarly => chosen
+                    .iter()
+                    .filter(|(course, _)| course.start <= EARLY)
+                    .count(),
+                Preference::FreeWednesday => chosen
+                    .iter()
+                    .filter(|(course, day)| *day == Weekday::Wed && course.end > AFTERNOON)
+                    .count(),
+                Preference::FewestDays => days,
+            })
+            .sum();
+
+        (score, days)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Weekday;
+
+    use super::{solve, Preference, Slot, Solution, PROPOSALS};
+    use crate::{
+        conflicts::overlapping,
+        timetable::models::{self, Category, Course},
+    };
+
+    fn course(name: &str, hours: (u32, u32)) -> Course {
+        models::course(name, Category::TD, hours)
+    }
+
+    /// Slots picked by the solution, with the fixed ones
+    fn chosen<'a>(
+        solution: &Solution,
+        fixed: &[Slot<'a>],
+        groups: &[Vec<Slot<'a>>],
+    ) -> Vec<Slot<'a>> {
+        solution
+            .picks
+            .iter()
+            .zip(groups)
+            .map(|(pick, group)| group[*pick])
+            .chain(fixed.iter().copied())
+            .collect()
+    }
+
+    #[test]
+    fn never_proposes_conflicts() {
+        let fixed_course = course("Fixed", (8, 10));
+        let courses = [
+            course("A", (9, 11)),
+            course("A", (8, 10)),
+            course("B", (9, 11)),
+            course("B", (13, 15)),
+        ];
+        let fixed = [(&fixed_course, Weekday::Mon)];
+        let groups = [
+            vec![(&courses[0], Weekday::Mon), (&courses[1], Weekday::Tue)],
+            vec![(&courses[2], Weekday::Tue), (&courses[3], Weekday::Tue)],
+        ];
+
+        let solutions = solve(&fixed, &groups, &[]);
+
+        assert!(!solutions.is_empty());
+        for solution in &solutions {
+            let slots = chosen(solution, &fixed, &groups);
+            for (index, (course, day)) in slots.iter().enumerate() {
+                let others = slots[index + 1..]
+                    .iter()
+                    .filter(|(_, other)| other == day)
+                    .map(|(other, _)| *other);
+                assert!(overlapping(course, others).is_empty());
+            }
+        }
+        // Only A on Tuesday fits, then B must avoid it
+        assert!(solutions.iter().all(|solution| solution.picks[0] == 1));
+        assert!(solutions.iter().all(|solution| solution.picks[1] == 1));
+    }
+
+    #[test]
+    fn no_early() {
+        let courses = [course("A", (8, 10)), course("A", (10, 12))];
+        let groups = [vec![
+            (&courses[0], Weekday::Mon),
+            (&courses[1], Weekday::Mon),
+        ]];
+
+        let solutions = solve(&[], &groups, &[Preference::NoEarly]);
+
+        assert_eq!(solutions[0].picks, [1]);
+        assert_eq!(solutions[0].score, 0);
+        assert_eq!(solutions[1].score, 1);
+    }
+
+    #[test]
+    fn free_wednesday() {
+        let courses = [course("A", (14, 16)), course("A", (14, 16))];
+        let groups = [vec![
+            (&courses[0], Weekday::Wed),
+            (&courses[1], Weekday::Thu),
+        ]];
+
+        let solutions = solve(&[], &groups, &[Preference::FreeWednesday]);
+
+        assert_eq!(solutions[0].picks, [1]);
+        assert_eq!(solutions[0].score, 0);
+    }
+
+    #[test]
+    fn fewest_days() {
+        let fixed_course = course("Fixed", (8, 10));
+        let courses = [course("A", (10, 12)), course("A", (10, 12))];
+        let fixed = [(&fixed_course, Weekday::Mon)];
+        let groups = [vec![
+            (&courses[0], Weekday::Tue),
+            (&courses[1], Weekday::Mon),
+        ]];
+
+        let solutions = solve(&fixed, &groups, &[Preference::FewestDays]);
+
+        assert_eq!(solutions[0].picks, [1]);
+        assert_eq!(solutions[0].days, 1);
+        assert_eq!(solutions[1].days, 2);
+    }
+
+    #[test]
+    fn results_are_capped() {
+        let courses = [course("A", (8, 10)), course("A", (10, 12))];
+        let days = [Weekday::Mon, Weekday::Tue, Weekday::Wed];
+        let group = days
+            .iter()
+            .flat_map(|day| courses.iter().map(move |course| (course, *day)))
+            .collect::<Vec<_>>();
+        // 6 × 6 × 6 combinations, some of them without any overlap
+        let groups = [group.clone(), group.clone(), group];
+
+        let solutions = solve(&[], &groups, &[Preference::FewestDays]);
+
+        assert_eq!(solutions.len(), PROPOSALS);
+        assert!(solutions
+            .windows(2)
+            .all(|pair| (pair[0].score, pair[0].days) <= (pair[1].score, pair[1].days)));
+    }
+
+    #[test]
+    fn subject_without_any_free_slot() {
+        let fixed_course = course("Fixed", (8, 12));
+        let courses = [course("A", (9, 10)), course("A", (10, 11))];
+        let fixed = [(&fixed_course, Weekday::Mon)];
+        let groups = [vec![
+            (&courses[0], Weekday::Mon),
+            (&courses[1], Weekday::Mon),
+        ]];
+
+        assert!(solve(&fixed, &groups, &[]).is_empty());
+    }
+}
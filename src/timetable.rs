@@ -1,35 +1,47 @@
 #![allow(clippy::cast_sign_loss)]
 
-use chrono::{Datelike, Duration, TimeZone, Utc};
+use chrono::{DateTime, Datelike, Duration, NaiveTime, TimeZone, Utc, Weekday};
 use regex::Regex;
-use scraper::Selector;
-use std::{collections::HashMap, sync::Arc};
+use scraper::{Html, Selector};
 
+use crate::i18n::Message;
 use crate::utils::{
-    format_time_slot, get_hours, get_semester, get_webpage, get_year,
+    fetcher::Fetcher,
+    get_semester, get_year,
     models::{Info, InfoList},
-    Capitalize,
 };
 
 use self::models::Day;
 
+pub mod grid;
 pub mod models;
 
+/// Duration of a course, in hours and minutes or only in minutes, a number
+/// without unit is rejected since it could be either
+const DURATION: &str =
+    r"^(?:(?P<hours>\d+)\s*h\s*(?:(?P<minutes>\d+)(?:\s*min)?)?|(?P<only_minutes>\d+)\s*min)$";
+
+/// Duration of a row of the webpage, in minutes, when no course tells it
+const DEFAULT_ROW_MINUTES: i64 = 15;
+
 /// Fetch the timetable for a class
 pub async fn timetable(
     level: i8,
     semester_opt: Option<i8>,
     year_opt: Option<i32>,
-    user_agent: &str,
-) -> models::Timetable {
+    fetcher: &Fetcher,
+) -> Result<models::Timetable, Box<dyn std::error::Error>> {
     let semester = get_semester(semester_opt);
 
     let year = get_year(year_opt, semester);
 
-    let document = get_webpage(level, semester, &year, user_agent)
-        .await
-        .expect("Can't reach timetable website.");
+    let document = fetcher.get_webpage(level, semester, &year).await?;
+
+    Ok(parse(&document, level, semester)?)
+}
 
+/// Parse the timetable webpage
+pub fn parse(document: &Html, level: i8, semester: i8) -> Result<models::Timetable, String> {
     // Selectors
     let sel_table = Selector::parse("table").unwrap();
     let sel_tbody = Selector::parse("tbody").unwrap();
@@ -38,135 +50,223 @@ pub async fn timetable(
     let sel_b = Selector::parse("b").unwrap();
     let sel_span = Selector::parse("span").unwrap();
 
+    /* TODO: Instead of searching *_M2, just find any TD_* and TP_* */
+    let re_title = Regex::new(
+        r"(?P<type>COURS|COURS_TD|TD|TD_M2|TP|TP_M2)? (?P<name>.*) : (?P<day>(lundi|mardi|mercredi|jeudi|vendredi|samedi|dimanche)) (?P<startime>.*) \(durée : (?P<duration>.*)\)",
+    )
+    .unwrap();
+    let re_duration = Regex::new(DURATION).unwrap();
+    let re_class = Regex::new(r"[ -][ML][1-3]$").unwrap();
+    let re_room =
+        Regex::new(r"(<table.*<\/table>|<br>.*?<br>.*?)?<br>(?P<location>.*?)<br>").unwrap();
+
     // Find the timetable
-    let raw_timetable = document.select(&sel_table).next().unwrap();
+    let raw_tbody = document
+        .select(&sel_table)
+        .next()
+        .and_then(|table| table.select(&sel_tbody).next())
+        .ok_or_else(|| Message::NoTimetable.to_string())?;
 
-    let schedules = get_hours();
+    // Duration of a row, the one of most courses
+    let row_minutes = row_minutes(raw_tbody.select(&sel_td).filter_map(|cell| {
+        let matches = re_title.captures(cell.value().attr("title")?)?;
+        let rowspan = cell.value().attr("rowspan")?.parse().ok()?;
+        Some((duration(&re_duration, &matches["duration"])?, rowspan))
+    }));
 
     let mut timetable: Vec<models::Day> = Vec::new();
 
-    raw_timetable
-        .select(&sel_tbody)
-        .next()
-        .unwrap()
-        .select(&sel_td)
-        .filter(|element| element.value().attr("title").is_some())
-        .for_each(|i| {
-            let extra_data = i.select(&sel_span).next().map(|span|
-                 span.inner_html().replace("<br>", "").trim().to_owned()
-            );
-
-            /* TODO: Instead of searching *_M2, just find any TD_* and TP_* */
-            let matches =
-                Regex::new(
-                    r"(?P<type>COURS|COURS_TD|TD|TD_M2|TP|TP_M2)? (?P<name>.*) : (?P<day>(lundi|mardi|mercredi|jeudi|vendredi)) (?P<startime>.*) \(durée : (?P<duration>.*)\)")
-                    .unwrap()
-                    .captures(i.value().attr("title").unwrap())
-                    .unwrap();
-
-            let day = matches
-                .name("day")
-                .unwrap()
-                .as_str()
-                .capitalize();
-
-            let startime = matches
-                .name("startime")
-                .unwrap()
-                .as_str();
-
-            let binding = i.select(&sel_b).last().unwrap().inner_html();
-            let course = models::Course{
-                category: match matches
-                .name("type")
-                .map_or("", |m| m.as_str()) {
-                    /* TODO: Instead of searching *_M2, just find any TD_* and TP_* */
-                    "COURS" => [models::Category::Cours].into(),
-                    "TP" | "TP_M2" => [models::Category::TP].into(),
-                    "TD" | "TD_M2" => [models::Category::TD].into(),
-                    "COURS_TD" => [models::Category::Cours, models::Category::TD].into(),
-                    _ => {
-                        println!("Unknown type of course, falling back to 'COURS': {}", i.value().attr("title").unwrap());
-                        [models::Category::Cours].into()
-                    },
-                },
-                name: Regex::new(r"[ -][ML][1-3]$").unwrap().replace(
-                    matches
-                        .name("name")
-                        .unwrap()
-                        .as_str(),
-                    ""
-                ).to_string(),
-                professor: if let Some(raw_prof) = i.select(&sel_small).last() {
-                        match raw_prof.inner_html() {
-                            i if i.starts_with("<span") => None,
-                            i => Some(i),
-                        }
-                    } else { None },
-                room: Regex::new(r"(<table.*<\/table>|<br>.*?<br>.*?)?<br>(?P<location>.*?)<br>")
-                    .unwrap()
-                    .captures(&binding)
-                    .unwrap().name("location")
-                    .unwrap()
-                    .as_str().to_owned(),
-                start: schedules.iter().position(|r| r.starts_with(startime)).unwrap(),
-                size: i.value().attr("rowspan").unwrap().parse::<usize>().unwrap(),
-                dtstart: None,
-                dtend: None,
-                data: extra_data,
-            };
-
-            // Search for the day in the timetable
-            if let Some(existing_day) = timetable.iter_mut().find(|x| x.name == day) {
-                existing_day.courses.push(Some(course));
+    for i in raw_tbody.select(&sel_td) {
+        let Some(title) = i.value().attr("title") else {
+            continue;
+        };
+
+        let extra_data = i
+            .select(&sel_span)
+            .next()
+            .map(|span| span.inner_html().replace("<br>", "").trim().to_owned());
+
+        let invalid = || Message::InvalidCell(title).to_string();
+
+        let matches = re_title.captures(title).ok_or_else(invalid)?;
+
+        let weekday = weekday(&matches["day"]);
+
+        let start =
+            NaiveTime::parse_from_str(&matches["startime"], "%Hh%M").map_err(|_| invalid())?;
+        let rows = Duration::minutes(
+            row_minutes
+                * i.value()
+                    .attr("rowspan")
+                    .and_then(|rowspan| rowspan.parse::<i64>().ok())
+                    .ok_or_else(invalid)?,
+        );
+
+        let (duration, warning) =
+            course_duration(title, duration(&re_duration, &matches["duration"]), rows);
+        if let Some(warning) = warning {
+            eprintln!("{warning}");
+        }
+
+        let binding = i.select(&sel_b).last().ok_or_else(invalid)?.inner_html();
+        let course = models::Course {
+            category: match matches.name("type").map_or("", |m| m.as_str()) {
+                /* TODO: Instead of searching *_M2, just find any TD_* and TP_* */
+                "COURS" => [models::Category::Cours].into(),
+                "TP" | "TP_M2" => [models::Category::TP].into(),
+                "TD" | "TD_M2" => [models::Category::TD].into(),
+                "COURS_TD" => [models::Category::Cours, models::Category::TD].into(),
+                _ => {
+                    eprintln!("{}", Message::UnknownCategory(title));
+                    [models::Category::Cours].into()
+                }
+            },
+            name: re_class.replace(&matches["name"], "").to_string(),
+            professor: if let Some(raw_prof) = i.select(&sel_small).last() {
+                match raw_prof.inner_html() {
+                    i if i.starts_with("<span") => None,
+                    i => Some(i),
+                }
+            } else {
+                None
+            },
+            room: re_room.captures(&binding).ok_or_else(invalid)?["location"].to_owned(),
+            start,
+            end: end(start, duration).ok_or_else(invalid)?,
+            dtstart: None,
+            dtend: None,
+            data: extra_data,
+            class: format!("M{level}"),
+            semester: semester as usize,
+        };
+
+        // Search for the day in the timetable
+        if let Some(existing_day) = timetable.iter_mut().find(|x| x.weekday == weekday) {
+            existing_day.courses.push(Some(course));
+        } else {
+            // Day doesn't exist, create a new Day
+            timetable.push(models::Day {
+                weekday,
+                courses: vec![Some(course)],
+            });
+        }
+    }
+
+    sort_days(&mut timetable);
+
+    Ok((grid::slots(&timetable), (semester as usize, timetable)))
+}
+
+/// Merge the timetables of multiple classes of the same semester
+pub fn merge(timetables: Vec<models::Timetable>) -> models::Timetable {
+    let mut timetables = timetables.into_iter();
+    let mut merged = timetables.next().expect("No timetable to merge.");
+
+    for timetable in timetables {
+        for day in timetable.1 .1 {
+            if let Some(existing_day) = merged.1 .1.iter_mut().find(|x| x.weekday == day.weekday) {
+                existing_day.courses.extend(day.courses);
             } else {
-                // Day with the name doesn't exist, create a new Day
-                timetable.push(models::Day {
-                    name: day.clone(),
-                    courses: vec![Some(course)],
-                });
+                merged.1 .1.push(day);
             }
-        });
+        }
+    }
 
-    // Sort by days
-    let day_positions = ["Lundi", "Mardi", "Mercredi", "Jeudi", "Vendredi"]
-        .iter()
-        .enumerate()
-        .map(|(i, &day)| (day.to_owned(), i))
-        .collect::<HashMap<String, usize>>();
-    timetable.sort_by(|a, b| day_positions[&a.name].cmp(&day_positions[&b.name]));
+    sort_days(&mut merged.1 .1);
+    merged.0 = grid::slots(&merged.1 .1);
 
-    (schedules, (semester as usize, timetable))
+    merged
 }
 
-/// Build the timetable
-pub fn build(timetable: &models::Timetable, dates: &Info) -> Vec<models::Course> {
-    let mut schedules = Vec::new();
-    // h1 => heure de début | m1 => minute de début
-    // h2 => heure de fin   | m2 => minute de fin
-    let re = Regex::new(r"(?P<h1>\d{1,2})h(?P<m1>\d{2})-(?P<h2>\d{1,2})h(?P<m2>\d{2})").unwrap();
-    for hour in timetable.0.iter() {
-        let captures = re.captures(hour).unwrap();
-
-        let h1 = match captures.name("h1") {
-            Some(h) => h.as_str().parse().unwrap(),
-            None => 0,
-        };
-        let m1 = match captures.name("m1") {
-            Some(h) => h.as_str().parse().unwrap(),
-            None => 0,
-        };
-        let h2 = match captures.name("h2") {
-            Some(h) => h.as_str().parse().unwrap(),
-            None => 0,
-        };
-        let m2 = match captures.name("m2") {
-            Some(h) => h.as_str().parse().unwrap(),
-            None => 0,
-        };
-        schedules.push(((h1, m1), (h2, m2)));
+/// Duration of a row of the webpage, in minutes, the most common one
+/// among the durations of the courses and their number of rows
+fn row_minutes(cells: impl Iterator<Item = (Duration, i64)>) -> i64 {
+    let mut counts = std::collections::BTreeMap::new();
+    for (duration, rows) in cells {
+        let minutes = duration.num_minutes();
+        if rows > 0 && minutes > 0 && minutes % rows == 0 {
+            *counts.entry(minutes / rows).or_insert(0) += 1;
+        }
+    }
+
+    counts
+        .into_iter()
+        .max_by_key(|(_, count)| *count)
+        .map_or(DEFAULT_ROW_MINUTES, |(minutes, _)| minutes)
+}
+
+/// Parse a duration of the website with the [`DURATION`] regex,
+/// i.e.: `1h30`, `2h` or `45min`
+fn duration(re: &Regex, text: &str) -> Option<Duration> {
+    let captures = re.captures(text.trim())?;
+    let number = |name| {
+        captures
+            .name(name)
+            .map_or(Some(0), |number| number.as_str().parse().ok())
+    };
+
+    Some(
+        Duration::hours(number("hours")?)
+            + Duration::minutes(number("minutes")? + number("only_minutes")?),
+    )
+}
+
+/// Duration of a course from the one stated in its title and the rows of its cell,
+/// with a warning when they differ or when the title doesn't tell it
+fn course_duration(
+    title: &str,
+    stated: Option<Duration>,
+    rows: Duration,
+) -> (Duration, Option<String>) {
+    match stated {
+        // The stated duration is authoritative, the cell may be split or rounded
+        Some(duration) if duration == rows => (duration, None),
+        Some(duration) => (
+            duration,
+            Some(Message::DurationMismatch(title, &hours(duration), &hours(rows)).to_string()),
+        ),
+        None => (rows, Some(Message::UnknownDuration(title).to_string())),
+    }
+}
+
+/// End of a course, none when it would go on past midnight
+fn end(start: NaiveTime, duration: Duration) -> Option<NaiveTime> {
+    match start.overflowing_add_signed(duration) {
+        (end, 0) => Some(end),
+        _ => None,
+    }
+}
+
+/// Format a duration, i.e.: 1h30
+fn hours(duration: Duration) -> String {
+    format!(
+        "{}h{:02}",
+        duration.num_hours(),
+        duration.num_minutes() % 60
+    )
+}
+
+/// Day of the week from its name on the website
+fn weekday(name: &str) -> Weekday {
+    match name {
+        "lundi" => Weekday::Mon,
+        "mardi" => Weekday::Tue,
+        "mercredi" => Weekday::Wed,
+        "jeudi" => Weekday::Thu,
+        "vendredi" => Weekday::Fri,
+        "samedi" => Weekday::Sat,
+        "dimanche" => Weekday::Sun,
+        _ => unreachable!("Unknown day: {name}"),
     }
+}
+
+/// Sort by days
+fn sort_days(timetable: &mut [Day]) {
+    timetable.sort_by_key(|day| day.weekday.num_days_from_monday());
+}
 
+/// Build the timetable
+pub fn build(timetable: &models::Timetable, dates: &Info) -> Vec<models::Course> {
     // Store all the courses for the semester
     let mut semester = Vec::new();
 
@@ -174,7 +274,6 @@ pub fn build(timetable: &models::Timetable, dates: &Info) -> Vec<models::Course>
     let datetimes = dates.get(&timetable.1 .0).unwrap();
     add_courses(
         &mut semester,
-        &schedules,
         &timetable.1 .1,
         &datetimes.course,
         Some(&vec![models::Category::Cours]),
@@ -182,7 +281,6 @@ pub fn build(timetable: &models::Timetable, dates: &Info) -> Vec<models::Course>
     );
     add_courses(
         &mut semester,
-        &schedules,
         &timetable.1 .1,
         &datetimes.td_tp,
         None,
@@ -192,14 +290,10 @@ pub fn build(timetable: &models::Timetable, dates: &Info) -> Vec<models::Course>
     semester
 }
 
-type Schedule = [((u32, u32), (u32, u32))];
-
 /// Add a course to the semester list
 fn add_courses(
     // Accumulator of courses of semester
     semester: &mut Vec<models::Course>,
-    // Hours used
-    schedules: &Schedule,
     // List of days
     days: &Vec<Day>,
     // Current courses list
@@ -210,18 +304,20 @@ fn add_courses(
     exclude: Option<&Vec<models::Category>>,
 ) {
     let before_break = info.first().unwrap();
-    let mut date = before_break.0;
+    let mut first_day = before_break.0;
     let mut rep = before_break.1;
     // For each weeks
     for _ in 0..2 {
+        let mut week = monday(first_day);
         for _ in 0..rep {
             for day in days {
-                for mut course in day.courses.iter().flatten().cloned() {
-                    // Get the hours
-                    let start = schedules.get(course.start).unwrap().0;
-                    // -1 because we only add when the size is > 1
-                    let end = schedules.get(course.start + course.size - 1).unwrap().1;
+                // Each course is placed on its own weekday
+                let date = week + Duration::days(day.weekday.num_days_from_monday().into());
+                if date < first_day {
+                    continue;
+                }
 
+                for mut course in day.courses.iter().flatten().cloned() {
                     // Check keep and exclude filters
                     if keep
                         .is_some_and(|list| !course.category.iter().any(|item| list.contains(item)))
@@ -233,65 +329,363 @@ fn add_courses(
                     }
 
                     // Add the changed datetimes
-                    course.dtstart = Some(
-                        Utc.with_ymd_and_hms(
-                            date.year(),
-                            date.month(),
-                            date.day(),
-                            start.0,
-                            start.1,
-                            0,
-                        )
-                        .unwrap(),
-                    );
-                    course.dtend = Some(
-                        Utc.with_ymd_and_hms(
-                            date.year(),
-                            date.month(),
-                            date.day(),
-                            end.0,
-                            end.1,
-                            0,
-                        )
-                        .unwrap(),
-                    );
+                    let date = date.date_naive();
+                    course.dtstart = Some(Utc.from_utc_datetime(&date.and_time(course.start)));
+                    course.dtend = Some(Utc.from_utc_datetime(&date.and_time(course.end)));
 
                     semester.push(course);
                 }
-                date += Duration::days(1);
             }
-            // From friday to monday
-            date += Duration::days(2);
+            week += Duration::weeks(1);
         }
         let after_break = info.last().unwrap();
-        date = after_break.0;
+        first_day = after_break.0;
         rep = after_break.1;
     }
 }
 
+/// Monday of the week of the date
+fn monday(date: DateTime<Utc>) -> DateTime<Utc> {
+    date - Duration::days(date.weekday().num_days_from_monday().into())
+}
+
 /// Display the timetable
-pub fn display(timetable: &(Arc<[String]>, (usize, Vec<Day>))) {
-    for day in &timetable.1 .1 {
-        for (index, course_option) in day.courses.iter().enumerate() {
-            if let Some(course) = course_option {
-                if index == 0 {
-                    println!("\n{}:", day.name);
-                }
+pub fn display(timetable: &models::Timetable, cell_length: usize) {
+    print!("{}", grid::render(timetable, cell_length));
+}
 
-                println!(
-                    "  {} - {} : {} ({}) // {}",
-                    format_time_slot(course.start, course.size),
-                    course
-                        .category
-                        .iter()
-                        .map(std::string::ToString::to_string)
-                        .collect::<Vec<String>>()
-                        .join(", "),
-                    course.name,
-                    course.room,
-                    course.professor.as_deref().unwrap_or("N/A"),
-                );
-            }
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use chrono::{DateTime, Duration, NaiveDate, NaiveTime, TimeZone, Utc, Weekday};
+    use regex::Regex;
+
+    use scraper::Html;
+
+    use super::{build, course_duration, duration, grid, models, parse, row_minutes, DURATION};
+    use crate::utils::models::{Info, InfoType};
+
+    /// Course of two hours starting at 8h30
+    fn course(name: &str, category: models::Category) -> models::Course {
+        let mut course = models::course(name, category, (8, 10));
+        (course.start, course.end) = (
+            NaiveTime::from_hms_opt(8, 30, 0).unwrap(),
+            NaiveTime::from_hms_opt(10, 30, 0).unwrap(),
+        );
+        course
+    }
+
+    fn day(weekday: Weekday, courses: Vec<models::Course>) -> models::Day {
+        models::Day {
+            weekday,
+            courses: courses.into_iter().map(Some).collect(),
         }
     }
+
+    fn timetable(days: Vec<models::Day>) -> models::Timetable {
+        (grid::slots(&days), (1, days))
+    }
+
+    fn date(year: i32, month: u32, day: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(year, month, day, 0, 0, 0).unwrap()
+    }
+
+    /// Two weeks of courses and TD/TP, starting at `first_day`, without break
+    fn info(first_day: DateTime<Utc>) -> Info {
+        let weeks = vec![(first_day, 2), (first_day + chrono::Duration::weeks(2), 0)];
+        HashMap::from([(
+            1,
+            InfoType {
+                course: weeks.clone(),
+                td_tp: weeks,
+            },
+        )])
+    }
+
+    /// Dates of the courses with the given name
+    fn dates(courses: &[models::Course], name: &str) -> Vec<NaiveDate> {
+        courses
+            .iter()
+            .filter(|course| course.name == name)
+            .map(|course| course.dtstart.unwrap().date_naive())
+            .collect()
+    }
+
+    #[test]
+    fn sparse_week() {
+        let timetable = timetable(vec![
+            day(Weekday::Mon, vec![course("A", models::Category::Cours)]),
+            day(Weekday::Thu, vec![course("B", models::Category::TD)]),
+        ]);
+
+        let courses = build(&timetable, &info(date(2026, 9, 14)));
+
+        assert_eq!(
+            dates(&courses, "A"),
+            [
+                NaiveDate::from_ymd_opt(2026, 9, 14).unwrap(),
+                NaiveDate::from_ymd_opt(2026, 9, 21).unwrap()
+            ]
+        );
+        assert_eq!(
+            dates(&courses, "B"),
+            [
+                NaiveDate::from_ymd_opt(2026, 9, 17).unwrap(),
+                NaiveDate::from_ymd_opt(2026, 9, 24).unwrap()
+            ]
+        );
+    }
+
+    #[test]
+    fn day_emptied_by_the_filters() {
+        let timetable = timetable(vec![
+            day(Weekday::Mon, vec![course("A", models::Category::Cours)]),
+            day(Weekday::Tue, vec![]),
+            day(Weekday::Wed, vec![]),
+            day(Weekday::Thu, vec![course("B", models::Category::TP)]),
+        ]);
+
+        let courses = build(&timetable, &info(date(2026, 9, 14)));
+
+        assert_eq!(
+            dates(&courses, "B")[0],
+            NaiveDate::from_ymd_opt(2026, 9, 17).unwrap()
+        );
+    }
+
+    #[test]
+    fn weekend() {
+        let timetable = timetable(vec![
+            day(Weekday::Fri, vec![course("A", models::Category::Cours)]),
+            day(Weekday::Sat, vec![course("B", models::Category::Cours)]),
+        ]);
+
+        let courses = build(&timetable, &info(date(2026, 9, 14)));
+
+        assert_eq!(
+            dates(&courses, "B")[0],
+            NaiveDate::from_ymd_opt(2026, 9, 19).unwrap()
+        );
+    }
+
+    #[test]
+    fn first_day_in_the_middle_of_the_week() {
+        let timetable = timetable(vec![
+            day(Weekday::Mon, vec![course("A", models::Category::Cours)]),
+            day(Weekday::Thu, vec![course("B", models::Category::Cours)]),
+        ]);
+
+        let courses = build(&timetable, &info(date(2026, 9, 16)));
+
+        // No course before the first day
+        assert_eq!(
+            dates(&courses, "A"),
+            [NaiveDate::from_ymd_opt(2026, 9, 21).unwrap()]
+        );
+        assert_eq!(
+            dates(&courses, "B")[0],
+            NaiveDate::from_ymd_opt(2026, 9, 17).unwrap()
+        );
+    }
+
+    #[test]
+    fn durations() {
+        let re = Regex::new(DURATION).unwrap();
+        let duration = |text| duration(&re, text);
+
+        assert_eq!(duration("2h00"), Some(Duration::hours(2)));
+        assert_eq!(duration("1h30"), Some(Duration::minutes(90)));
+        assert_eq!(duration("1h30min"), Some(Duration::minutes(90)));
+        assert_eq!(duration("2h"), Some(Duration::hours(2)));
+        assert_eq!(duration("3h"), Some(Duration::hours(3)));
+        assert_eq!(duration(" 2 h 15 "), Some(Duration::minutes(135)));
+        assert_eq!(duration("45min"), Some(Duration::minutes(45)));
+        // Minutes or hours, it can't be told
+        assert_eq!(duration("90"), None);
+        assert_eq!(duration("?"), None);
+        assert_eq!(duration(""), None);
+        assert_eq!(duration("abc"), None);
+        assert_eq!(duration("1h3x"), None);
+        assert_eq!(duration("h30"), None);
+        assert_eq!(duration("1h30 2h"), None);
+    }
+
+    #[test]
+    fn stated_durations() {
+        let title = "TP Réseau M1 : mardi 14h00 (durée : 3h00)";
+
+        assert_eq!(
+            course_duration(title, Some(Duration::hours(3)), Duration::hours(3)),
+            (Duration::hours(3), None)
+        );
+
+        // The cell is shorter than the course
+        let (duration, warning) =
+            course_duration(title, Some(Duration::hours(3)), Duration::minutes(150));
+        assert_eq!(duration, Duration::hours(3));
+        let warning = warning.unwrap();
+        assert!(warning.contains(title) && warning.contains("3h00") && warning.contains("2h30"));
+
+        // Read from the cell when the title doesn't tell it
+        let (duration, warning) = course_duration(title, None, Duration::minutes(90));
+        assert_eq!(duration, Duration::minutes(90));
+        assert!(warning.unwrap().contains(title));
+    }
+
+    #[test]
+    fn rows_of_the_page() {
+        let cells = |cells: &[(i64, i64)]| {
+            cells
+                .iter()
+                .map(|(minutes, rows)| (Duration::minutes(*minutes), *rows))
+                .collect::<Vec<_>>()
+                .into_iter()
+        };
+
+        assert_eq!(row_minutes(cells(&[(90, 6), (120, 8), (105, 6)])), 15);
+        assert_eq!(row_minutes(cells(&[(90, 3), (120, 4), (60, 4)])), 30);
+        assert_eq!(row_minutes(cells(&[(90, 0)])), 15);
+        assert_eq!(row_minutes(cells(&[])), 15);
+    }
+
+    #[test]
+    fn grid_follows_the_courses() {
+        let time = |hour, minute| NaiveTime::from_hms_opt(hour, minute, 0).unwrap();
+        let mut early = course("A", models::Category::Cours);
+        (early.start, early.end) = (time(7, 45), time(8, 15));
+        let mut late = course("B", models::Category::TP);
+        (late.start, late.end) = (time(20, 35), time(21, 15));
+
+        let slots = grid::slots(&[day(Weekday::Mon, vec![early.clone(), late])]);
+
+        // Rows of 10 minutes to align 7h45 with 20h35
+        assert_eq!(slots.first(), Some(&time(7, 45)));
+        assert_eq!(slots.last(), Some(&time(21, 5)));
+        assert_eq!(slots[1], time(7, 55));
+        assert_eq!(grid::rows(&slots, &early), (0, 3));
+    }
+
+    #[test]
+    fn odd_times_are_rounded() {
+        let time = |hour, minute| NaiveTime::from_hms_opt(hour, minute, 0).unwrap();
+        let mut first = course("A", models::Category::Cours);
+        (first.start, first.end) = (time(8, 0), time(9, 0));
+        let mut odd = course("B", models::Category::TD);
+        (odd.start, odd.end) = (time(9, 7), time(10, 3));
+        let mut short = course("C", models::Category::TP);
+        (short.start, short.end) = (time(8, 0), time(8, 1));
+
+        let slots = grid::slots(&[day(Weekday::Mon, vec![first, odd.clone(), short.clone()])]);
+
+        // Rows of 5 minutes at least, the times are rounded to them
+        assert_eq!(slots[1], time(8, 5));
+        assert_eq!(slots[grid::rows(&slots, &odd).0], time(9, 5));
+        assert_eq!(grid::rows(&slots, &odd).1, slots.len());
+        assert_eq!(grid::rows(&slots, &short), (0, 1));
+    }
+
+    #[test]
+    fn filters_remove_emptied_days() {
+        let timetable = timetable(vec![
+            day(Weekday::Mon, vec![course("A", models::Category::Cours)]),
+            models::Day {
+                weekday: Weekday::Tue,
+                courses: vec![None],
+            },
+            day(Weekday::Thu, vec![course("B", models::Category::TD)]),
+        ]);
+
+        let filtered = crate::filter::timetable(timetable, false, None, false);
+
+        assert_eq!(
+            filtered
+                .1
+                 .1
+                .iter()
+                .map(|day| day.weekday)
+                .collect::<Vec<_>>(),
+            [Weekday::Mon, Weekday::Thu]
+        );
+    }
+
+    #[test]
+    fn webpage() {
+        // Rows of 30 minutes, the duration of the TP is unknown
+        let page = Html::parse_document(
+            "<html><body><table><tbody><tr>\
+            <td title=\"COURS Compilation M1 : lundi 8h30 (durée : 2h00)\" rowspan=\"4\">\
+            <b>Compilation</b><b>x<br>2031<br></b><small>M. Prof</small></td>\
+            <td title=\"TD Compilation M1 : samedi 9h00 (durée : 1h30)\" rowspan=\"3\">\
+            <b>x<br>1001<br></b><span>Groupe 1<br></span></td>\
+            <td title=\"TP Réseau M1 : mardi 14h00 (durée : ?)\" rowspan=\"3\">\
+            <b>x<br>SALLE TP<br></b></td>\
+            </tr></tbody></table></body></html>",
+        );
+
+        let (_, (semester, days)) = parse(&page, 1, 1).unwrap();
+        assert_eq!(semester, 1);
+
+        let time = |hour, minute| NaiveTime::from_hms_opt(hour, minute, 0).unwrap();
+        let courses = days
+            .iter()
+            .flat_map(|day| {
+                day.courses.iter().flatten().map(|course| {
+                    (
+                        day.weekday,
+                        course.name.as_str(),
+                        course.room.as_str(),
+                        course.professor.as_deref(),
+                        course.data.as_deref(),
+                        course.start,
+                        course.end,
+                    )
+                })
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            courses,
+            [
+                (
+                    Weekday::Mon,
+                    "Compilation",
+                    "2031",
+                    Some("M. Prof"),
+                    None,
+                    time(8, 30),
+                    time(10, 30)
+                ),
+                (
+                    Weekday::Tue,
+                    "Réseau",
+                    "SALLE TP",
+                    None,
+                    None,
+                    time(14, 0),
+                    time(15, 30)
+                ),
+                (
+                    Weekday::Sat,
+                    "Compilation",
+                    "1001",
+                    None,
+                    Some("Groupe 1"),
+                    time(9, 0),
+                    time(10, 30)
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn course_past_midnight() {
+        let page = Html::parse_document(
+            "<html><body><table><tbody><tr>\
+            <td title=\"COURS Nuit M1 : lundi 23h00 (durée : 2h00)\" rowspan=\"8\">\
+            <b>x<br>2031<br></b></td>\
+            </tr></tbody></table></body></html>",
+        );
+
+        assert!(parse(&page, 1, 1).unwrap_err().contains("lundi 23h00"));
+    }
 }
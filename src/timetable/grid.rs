@@ -0,0 +1,319 @@
+use std::{fmt::Write, io::IsTerminal, sync::Arc};
+
+use chrono::{NaiveTime, Timelike};
+
+use crate::{export::hour, i18n, utils::format_time_slot};
+
+use super::models::{Category, Course, Day, Timetable};
+
+/// Smallest width a lane can have, borders included
+const MIN_LANE_WIDTH: usize = 5;
+
+/// Longest duration of a row, in minutes, so the courses are tall enough to show their details
+const MAX_STEP: u32 = 15;
+
+/// Shortest duration of a row, in minutes, so odd times don't make the grid huge
+const MIN_STEP: u32 = 5;
+
+/// Start of the rows of the grid, from the first course to the end of the last one,
+/// with rows small enough so every course starts and ends on a row, the courses
+/// at odd times are rounded to the nearest row
+pub fn slots(days: &[Day]) -> Arc<[NaiveTime]> {
+    let minutes = days
+        .iter()
+        .flat_map(|day| day.courses.iter().flatten())
+        .flat_map(|course| [course.start, course.end])
+        .map(|time| time.num_seconds_from_midnight() / 60)
+        .collect::<Vec<_>>();
+    let (Some(first), Some(last)) = (minutes.iter().min(), minutes.iter().max()) else {
+        return [].into();
+    };
+
+    // Largest duration dividing the gaps between every start and end
+    let gaps = minutes.iter().fold(0, |step, time| gcd(step, time - first));
+    let step = (MIN_STEP..=MAX_STEP)
+        .rev()
+        .find(|step| gaps % step == 0)
+        .unwrap_or(MIN_STEP);
+
+    (*first..*last)
+        .step_by(step as usize)
+        .filter_map(|minute| NaiveTime::from_hms_opt(minute / 60, minute % 60, 0))
+        .collect()
+}
+
+/// Greatest common divisor
+fn gcd(a: u32, b: u32) -> u32 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// Rows of the grid taken by the course, as `[first, last)`, rounded to the
+/// nearest rows and never empty
+pub fn rows(slots: &[NaiveTime], course: &Course) -> (usize, usize) {
+    let half = match slots {
+        [first, second, ..] => (*second - *first) / 2,
+        _ => chrono::Duration::zero(),
+    };
+    let nearest = |time: NaiveTime| slots.partition_point(|slot| *slot + half < time);
+
+    let first = nearest(course.start).min(slots.len().saturating_sub(1));
+    (first, nearest(course.end).max(first + 1))
+}
+
+/// Render the timetable as a weekly grid, one column per day
+/// and one row per time slot
+pub fn render(timetable: &Timetable, cell_length: usize) -> String {
+    let slots = &timetable.0;
+    let days = &timetable.1 .1;
+
+    // Only show the rows between the first and the last course of the week
+    let courses = days
+        .iter()
+        .flat_map(|day| day.courses.iter().flatten())
+        .map(|course| rows(slots, course))
+        .collect::<Vec<_>>();
+    let (Some(first_row), Some(last_row)) = (
+        courses.iter().map(|(first, _)| *first).min(),
+        courses.iter().map(|(_, last)| *last).max(),
+    ) else {
+        return String::new();
+    };
+
+    let colors = use_colors();
+
+    // Labels of the rows, only the start of each slot
+    let labels = slots.iter().map(|slot| hour(*slot)).collect::<Vec<_>>();
+    let label_width = labels
+        .iter()
+        .map(|label| label.chars().count())
+        .max()
+        .unwrap_or(0);
+
+    let lanes = days
+        .iter()
+        .map(|day| assign_lanes(day.courses.iter().flatten()))
+        .collect::<Vec<_>>();
+
+    // Days with many lanes are widened so every lane stays readable
+    let widths = lanes
+        .iter()
+        .map(|day_lanes| column_width(day_lanes.len(), cell_length))
+        .collect::<Vec<_>>();
+
+    let mut output = String::new();
+
+    // Header with the days
+    output.push_str(&" ".repeat(label_width));
+    for (day, width) in days.iter().zip(&widths) {
+        let _ = write!(output, "│{}", center(i18n::weekday(day.weekday), *width));
+    }
+    output.push('\n');
+    output.push_str(&"─".repeat(label_width));
+    for width in &widths {
+        let _ = write!(output, "┼{}", "─".repeat(*width));
+    }
+    output.push('\n');
+
+    for (row, label) in labels.iter().enumerate().take(last_row).skip(first_row) {
+        output.push_str(&pad(label, label_width));
+        for (day_lanes, width) in lanes.iter().zip(&widths) {
+            output.push('│');
+            output.push_str(&render_row(day_lanes, slots, row, *width, colors));
+        }
+        output.push('\n');
+    }
+
+    output
+}
+
+/// Width of a day, at least `cell_length` and enough for its lanes
+fn column_width(lanes: usize, cell_length: usize) -> usize {
+    cell_length.max(lanes * MIN_LANE_WIDTH + lanes.saturating_sub(1))
+}
+
+/// Split the courses of a day in lanes, so overlapping courses
+/// are drawn side by side
+pub fn assign_lanes<'a>(courses: impl Iterator<Item = &'a Course>) -> Vec<Vec<&'a Course>> {
+    let mut sorted = courses.collect::<Vec<_>>();
+    sorted.sort_by_key(|course| (course.start, course.end));
+
+    let mut lanes: Vec<Vec<&Course>> = vec![];
+    for course in sorted {
+        // First lane where the previous course is already over
+        match lanes
+            .iter_mut()
+            .find(|lane| lane.last().is_none_or(|last| last.end <= course.start))
+        {
+            Some(lane) => lane.push(course),
+            None => lanes.push(vec![course]),
+        }
+    }
+
+    lanes
+}
+
+/// Render one row of a day, splitting the available width between its lanes
+fn render_row(
+    lanes: &[Vec<&Course>],
+    slots: &[NaiveTime],
+    row: usize,
+    cell_length: usize,
+    colors: bool,
+) -> String {
+    if lanes.is_empty() {
+        return " ".repeat(cell_length);
+    }
+
+    let count = lanes.len();
+    let width = (cell_length - (count - 1)) / count;
+
+    let mut cells = lanes
+        .iter()
+        .map(|lane| {
+            lane.iter()
+                .map(|course| (course, rows(slots, course)))
+                .find(|(_, (first, last))| *first <= row && row < *last)
+                .map_or_else(
+                    || " ".repeat(width),
+                    |(course, (first, last))| {
+                        let cell = render_cell(course, row - first, last - first, width);
+                        if colors {
+                            colorize(&cell, course)
+                        } else {
+                            cell
+                        }
+                    },
+                )
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    // Fill the remaining space left by the integer division
+    let used = count * width + count - 1;
+    if used < cell_length {
+        cells.push_str(&" ".repeat(cell_length - used));
+    }
+
+    cells
+}
+
+/// Render the line of a course box at the given offset from its start,
+/// the course taking `size` rows
+fn render_cell(course: &Course, offset: usize, size: usize, width: usize) -> String {
+    let inner = width - 2;
+    let lines = [
+        format!(
+            "{} - {}",
+            course
+                .category
+                .iter()
+                .map(std::string::ToString::to_string)
+                .collect::<Vec<String>>()
+                .join("/"),
+            course.name
+        ),
+        format_time_slot(course.start, course.end),
+        course.room.clone(),
+        course.professor.clone().unwrap_or_default(),
+    ];
+
+    // Too small to draw a box, only show text between brackets
+    if size <= 2 {
+        let text = lines.get(offset).map_or("", String::as_str);
+        return format!("[{}]", pad(text, inner));
+    }
+
+    if offset == 0 {
+        format!("┌{}┐", "─".repeat(inner))
+    } else if offset == size - 1 {
+        format!("└{}┘", "─".repeat(inner))
+    } else {
+        let text = lines.get(offset - 1).map_or("", String::as_str);
+        format!("│{}│", pad(text, inner))
+    }
+}
+
+/// Color of a course, based on its first category
+fn colorize(cell: &str, course: &Course) -> String {
+    let code = match course.category.first() {
+        Some(Category::Cours) | None => 34,
+        Some(Category::TD) => 32,
+        Some(Category::TP) => 35,
+    };
+
+    format!("\x1b[{code}m{cell}\x1b[0m")
+}
+
+/// Colors are only used when printing to a terminal
+fn use_colors() -> bool {
+    std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none()
+}
+
+/// Truncate or pad the text to exactly fit the width
+fn pad(text: &str, width: usize) -> String {
+    let length = text.chars().count();
+    if length > width {
+        let mut truncated = text
+            .chars()
+            .take(width.saturating_sub(1))
+            .collect::<String>();
+        if width > 0 {
+            truncated.push('…');
+        }
+        truncated
+    } else {
+        format!("{text}{}", " ".repeat(width - length))
+    }
+}
+
+/// Center the text in the width
+fn center(text: &str, width: usize) -> String {
+    let length = text.chars().count();
+    if length >= width {
+        return pad(text, width);
+    }
+
+    let left = (width - length) / 2;
+    pad(&format!("{}{text}", " ".repeat(left)), width)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Weekday;
+
+    use super::{render, slots};
+    use crate::timetable::models::{self, Category, Course, Day};
+
+    fn course(hour: u32) -> Course {
+        models::course(&format!("Cours {hour}"), Category::TD, (hour, 13))
+    }
+
+    #[test]
+    fn many_lanes_keep_the_columns_aligned() {
+        let days = vec![
+            Day {
+                weekday: Weekday::Mon,
+                courses: (8..12).map(|hour| Some(course(hour))).collect(),
+            },
+            Day {
+                weekday: Weekday::Tue,
+                courses: vec![Some(course(8))],
+            },
+        ];
+        let timetable = (slots(&days), (1, days));
+
+        let output = render(&timetable, 10);
+
+        let widths = output
+            .lines()
+            .map(|line| line.chars().count())
+            .collect::<Vec<_>>();
+        assert!(widths.iter().all(|width| *width == widths[0]), "{output}");
+        // Labels, then four lanes of five characters with their separators, then Tuesday
+        assert_eq!(widths[0], 5 + 1 + 23 + 1 + 10);
+    }
+}
@@ -1,6 +1,9 @@
 use std::sync::Arc;
 
-#[derive(Clone, Debug, PartialEq)]
+use chrono::{NaiveTime, Weekday};
+use serde::Serialize;
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
 pub enum Category {
     Cours,
     TP,
@@ -13,9 +16,9 @@ impl std::fmt::Display for Category {
     }
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize)]
 pub struct Course {
-    /// Type du cours
+    /// Type of course
     pub category: Arc<[Category]>,
 
     /// Course's name
@@ -27,14 +30,11 @@ pub struct Course {
     /// List of rooms where the course takes place
     pub room: String,
 
-    /// Time the course starts, as a number :
-    /// - 0 => first possible class of the day
-    /// - 1 => second possible class of the day
-    /// - etc.
-    pub start: usize,
+    /// Time the course starts
+    pub start: NaiveTime,
 
-    /// Number of time slots the course takes up in the timetable
-    pub size: usize,
+    /// Time the course ends
+    pub end: NaiveTime,
 
     /// Datetime when the course start
     /// Filled only when building for the ICS
@@ -46,20 +46,44 @@ pub struct Course {
 
     /// Extra data
     pub data: Option<String>,
+
+    /// Class the course comes from, i.e.: M1
+    pub class: String,
+
+    /// Semester the course belongs to
+    pub semester: usize,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct Day {
-    /// Day's name
-    pub name: String,
+    /// Day of the week
+    pub weekday: Weekday,
     /// Ordered list of all the courses of the day
     pub courses: Vec<Option<Course>>,
 }
 
 // Data builded in the timetable webpage
 pub type Timetable = (
-    // Schedules
-    Arc<[String]>,
+    // Start of the rows of the weekly grid
+    Arc<[NaiveTime]>,
     // Timetable per days with the semester as the key
     (usize, Vec<Day>),
 );
+
+/// Course of the first semester of the M1, from and to the given hours, for the tests
+#[cfg(test)]
+pub(crate) fn course(name: &str, category: Category, (start, end): (u32, u32)) -> Course {
+    Course {
+        category: [category].into(),
+        name: name.to_owned(),
+        professor: None,
+        room: String::new(),
+        start: NaiveTime::from_hms_opt(start, 0, 0).unwrap(),
+        end: NaiveTime::from_hms_opt(end, 0, 0).unwrap(),
+        dtstart: None,
+        dtend: None,
+        data: None,
+        class: "M1".to_owned(),
+        semester: 1,
+    }
+}
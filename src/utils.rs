@@ -1,49 +1,29 @@
-use std::{collections::HashMap, sync::Arc, time::Duration};
+use std::collections::HashMap;
 
-use chrono::{Datelike, Utc};
-use scraper::Html;
+use chrono::{Datelike, NaiveTime, Utc, Weekday};
 
-use crate::timetable::models::{Category, Course, Timetable};
+use crate::{
+    export::hour,
+    i18n,
+    timetable::models::{Category, Course, Timetable},
+};
 
+pub mod cache;
+pub mod fetcher;
+pub mod file;
 pub mod models;
+#[cfg(test)]
+pub mod server;
 
-/// Panic if an error happened
-pub fn check_errors(html: &String, loc: &str) {
+/// Returns an error if the page reports one
+pub fn check_errors(html: &str, loc: &str) -> Result<(), String> {
     let no_timetable = "Aucun créneau horaire affecté";
     match html {
-        t if t.contains(no_timetable) => panic!("URL: {loc} • {no_timetable}"),
-        _ => (),
+        t if t.contains(no_timetable) => Err(format!("URL: {loc} • {no_timetable}")),
+        _ => Ok(()),
     }
 }
 
-/// Get timetable webpage
-pub async fn get_webpage(
-    level: i8,
-    semester: i8,
-    year: &str,
-    user_agent: &str,
-) -> Result<Html, Box<dyn std::error::Error>> {
-    let url = format!("https://silice.informatique.univ-paris-diderot.fr/ufr/U{year}/EDT/visualiserEmploiDuTemps.php?quoi=M{level},{semester}");
-
-    // Use custom User-Agent
-    let client = reqwest::Client::builder().user_agent(user_agent).build()?;
-    let html = client
-        .get(&url)
-        .timeout(Duration::from_secs(5))
-        .send()
-        .await?
-        .text()
-        .await?;
-
-    // Panic on error
-    crate::utils::check_errors(&html, &url);
-
-    // Parse document
-    let document = Html::parse_document(&html);
-
-    Ok(document)
-}
-
 /// Get the current semester depending on the current date
 pub fn get_semester(semester: Option<i8>) -> i8 {
     match semester {
@@ -78,73 +58,42 @@ pub fn get_year(year: Option<i32>, semester: i8) -> String {
     }
 }
 
-pub trait Capitalize {
-    /// Capitalize string
-    fn capitalize(&self) -> String;
-}
-
-impl Capitalize for str {
-    fn capitalize(&self) -> String {
-        let mut string = self.to_owned();
-        if let Some(r) = string.get_mut(0..1) {
-            r.make_ascii_uppercase();
-        }
-
-        string
+/// School year starting in the asked year, or the current one
+pub fn get_school_year(year: Option<i32>) -> String {
+    match year {
+        Some(n) => format!("{}-{}", n, n + 1),
+        None => get_year(None, get_semester(None)),
     }
 }
 
-/// Get all hours used the source, from 08:00 to at least 20:00
-pub fn get_hours() -> Arc<[String]> {
-    let mut hours = vec![];
-    for hour in 8..=20 {
-        for minute in &[0, 15, 30, 45] {
-            let hour_str = format!("{hour}h{minute:02}");
-            if let Some(last_hour) = hours.pop() {
-                hours.push(format!("{last_hour}-{hour_str}"));
-            }
-            hours.push(hour_str);
-        }
-    }
-    for _ in 0..4 {
-        hours.pop();
-    }
-
-    hours.into()
-}
-
 /// Names showed to the users
-pub fn get_selection(data: &(&Course, String)) -> String {
-    let hours = get_hours();
-
+pub fn get_selection(data: &(&Course, Weekday)) -> String {
     format!(
-        "{} - {} {}-{}",
+        "[{}] {} - {} {}-{}",
+        data.0.class,
         data.0.name,
-        data.1,
-        hours[data.0.start].split_once('-').unwrap().0,
-        hours[data.0.start + data.0.size - 1]
-            .split_once('-')
-            .unwrap()
-            .1
+        i18n::weekday(data.1),
+        hour(data.0.start),
+        hour(data.0.end)
     )
 }
 
 /// Entry's name used for finding duplicates
 pub fn get_entry(course: &Course) -> String {
-    format!("{} - {:?}", course.name, course.category)
+    format!("{} - {} - {:?}", course.class, course.name, course.category)
 }
 
 /// Entry's name used for finding duplicates, ignoring categories
 pub fn get_entry_nocat(course: &Course) -> String {
-    course.name.clone()
+    format!("{} - {}", course.class, course.name)
 }
 
 /// Returns a couple of (list of courses) and (a hashmap of how much they appears in the vector)
 pub fn get_count<'a>(
-    timetable: &'a mut Timetable,
+    timetable: &'a Timetable,
     allowed_list: &'a [Category],
     getter: fn(&Course) -> String,
-) -> (Vec<(&'a Course, String)>, HashMap<String, i32>) {
+) -> (Vec<(&'a Course, Weekday)>, HashMap<String, i32>) {
     // List of courses who will be courses
     let mut courses = vec![];
 
@@ -157,7 +106,7 @@ pub fn get_count<'a>(
                     .iter()
                     .any(|category| allowed_list.contains(category))
                 {
-                    courses.push((course, day.name.clone()));
+                    courses.push((course, day.weekday));
                     let count = counts.entry(getter(course)).or_insert(0);
                     *count += 1;
                 }
@@ -168,13 +117,9 @@ pub fn get_count<'a>(
     (courses, counts)
 }
 
-pub fn format_time_slot(start: usize, size: usize) -> String {
-    let start_hour = 8 + (start * 15) / 60;
-    let start_minute = (start * 15) % 60;
-    let end_hour = start_hour + (size * 15) / 60;
-    let end_minute = (start_minute + (size * 15)) % 60;
-
-    format!("{start_hour:02}h{start_minute:02}-{end_hour:02}h{end_minute:02}")
+/// Format a slot of the timetable as a time range
+pub fn format_time_slot(start: NaiveTime, end: NaiveTime) -> String {
+    format!("{}-{}", start.format("%Hh%M"), end.format("%Hh%M"))
 }
 
 /// Based on the user input and some default values, find the correct repartition of weeks between the break
@@ -0,0 +1,108 @@
+use std::{
+    fmt::Write,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use super::file::write_atomic;
+
+/// How long a cached page is used without asking the server
+// `Duration::from_hours` would raise the minimum version of Rust
+#[allow(clippy::duration_suboptimal_units)]
+const TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// A page stored in the cache
+pub struct Entry {
+    /// Raw HTML of the page
+    pub html: String,
+    /// `ETag` header sent by the server
+    pub etag: Option<String>,
+    /// `Last-Modified` header sent by the server
+    pub last_modified: Option<String>,
+    /// When the page was last checked against the server
+    pub fetched: SystemTime,
+}
+
+impl Entry {
+    /// If the page can be used without asking the server
+    pub fn is_fresh(&self) -> bool {
+        self.fetched.elapsed().is_ok_and(|elapsed| elapsed < TTL)
+    }
+}
+
+/// Directory of the cache, following the XDG specification
+pub fn directory() -> Option<PathBuf> {
+    std::env::var_os("XDG_CACHE_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))
+        .map(|dir| dir.join("cal7tor"))
+}
+
+/// Load a page from the cache
+pub fn load(dir: &Path, key: &str) -> Option<Entry> {
+    let base = dir.join(key);
+
+    let html = std::fs::read_to_string(base.with_extension("html")).ok()?;
+    let meta = std::fs::read_to_string(base.with_extension("meta")).ok()?;
+
+    let mut entry = Entry {
+        html,
+        etag: None,
+        last_modified: None,
+        fetched: UNIX_EPOCH,
+    };
+    for line in meta.lines() {
+        match line.split_once('=') {
+            Some(("etag", value)) => entry.etag = Some(value.to_owned()),
+            Some(("last-modified", value)) => entry.last_modified = Some(value.to_owned()),
+            Some(("fetched", value)) => {
+                entry.fetched = UNIX_EPOCH + Duration::from_secs(value.parse().ok()?);
+            }
+            _ => (),
+        }
+    }
+
+    Some(entry)
+}
+
+/// Store a page in the cache, failures are ignored since the cache is optional
+pub fn store(dir: &Path, key: &str, entry: &Entry) {
+    if std::fs::create_dir_all(dir).is_err() {
+        return;
+    }
+    let base = dir.join(key);
+
+    let mut meta = format!(
+        "fetched={}\n",
+        entry
+            .fetched
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs()
+    );
+    if let Some(etag) = &entry.etag {
+        let _ = writeln!(meta, "etag={etag}");
+    }
+    if let Some(last_modified) = &entry.last_modified {
+        let _ = writeln!(meta, "last-modified={last_modified}");
+    }
+
+    // The metadata is written last, so it never describes a page not stored yet
+    let _ = write_atomic(&base.with_extension("html"), &entry.html)
+        .and_then(|()| write_atomic(&base.with_extension("meta"), &meta));
+}
+
+/// Load a file of the cache, i.e.: the state of a `CalDAV` collection
+pub fn read(dir: &Path, name: &str) -> Option<String> {
+    std::fs::read_to_string(dir.join(name)).ok()
+}
+
+/// Store a file in the cache, failures are ignored since the cache is optional
+pub fn write(dir: &Path, name: &str, content: &str) {
+    if std::fs::create_dir_all(dir).is_err() {
+        return;
+    }
+
+    let _ = write_atomic(&dir.join(name), content);
+}
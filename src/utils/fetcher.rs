@@ -0,0 +1,440 @@
+use std::{
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use reqwest::{
+    header::{HeaderValue, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED},
+    Client, Proxy, RequestBuilder, Response, StatusCode,
+};
+use scraper::Html;
+
+use super::{cache, check_errors};
+use crate::i18n::Message;
+
+/// Where the timetables are published
+pub const DEFAULT_BASE_URL: &str = "https://silice.informatique.univ-paris-diderot.fr/ufr";
+
+/// Delay before the first retry, doubled after each attempt
+const BACKOFF: Duration = Duration::from_millis(500);
+
+/// Raw HTML of a timetable webpage
+pub struct Page {
+    /// Content of the page
+    pub html: String,
+    /// Outdated copy from the cache, used since the server failed
+    pub stale: bool,
+}
+
+/// Fetch the timetable webpages
+pub struct Fetcher {
+    /// HTTP client, shared by all the requests
+    client: Client,
+    /// Base URL of the timetables
+    base_url: String,
+    /// Number of retries on transient errors
+    retries: u32,
+    /// Directory of the cache, none when it can't be found
+    cache: Option<PathBuf>,
+    /// Ignore the cached pages
+    refresh: bool,
+    /// Archive used instead of the website
+    offline: Option<PathBuf>,
+}
+
+impl Fetcher {
+    /// Build the fetcher, `base_url` defaults to [`DEFAULT_BASE_URL`],
+    /// pages are read from the `offline` archive when given
+    pub fn new(
+        user_agent: &str,
+        timeout: Duration,
+        proxy: Option<&str>,
+        base_url: Option<&str>,
+        retries: u32,
+        refresh: bool,
+        offline: Option<PathBuf>,
+    ) -> Result<Self, reqwest::Error> {
+        // Use custom User-Agent
+        let mut builder = Client::builder().user_agent(user_agent).timeout(timeout);
+        if let Some(proxy) = proxy {
+            builder = builder.proxy(Proxy::all(proxy)?);
+        }
+
+        Ok(Self {
+            client: builder.build()?,
+            base_url: base_url
+                .unwrap_or(DEFAULT_BASE_URL)
+                .trim_end_matches('/')
+                .to_owned(),
+            retries,
+            cache: cache::directory(),
+            refresh,
+            offline,
+        })
+    }
+
+    /// Use another directory for the cache
+    #[cfg(test)]
+    pub fn with_cache(self, cache: Option<PathBuf>) -> Self {
+        Self { cache, ..self }
+    }
+
+    /// Directory of the cache, to store other files in it
+    pub fn cache(&self) -> Option<&Path> {
+        self.cache.as_deref()
+    }
+
+    /// HTTP client, to reuse its settings for other servers
+    pub fn client(&self) -> &Client {
+        &self.client
+    }
+
+    /// Name of the page in an archive
+    pub fn page_name(level: i8, semester: i8) -> String {
+        format!("M{level}-S{semester}.html")
+    }
+
+    /// Name of the page in the cache
+    fn cache_key(&self, level: i8, semester: i8, year: &str) -> String {
+        // Pages from another server are cached apart
+        if self.base_url == DEFAULT_BASE_URL {
+            format!("{year}-M{level}-S{semester}")
+        } else {
+            format!(
+                "{}-{year}-M{level}-S{semester}",
+                self.base_url
+                    .replace(|c: char| !c.is_ascii_alphanumeric(), "_")
+            )
+        }
+    }
+
+    /// Get timetable webpage, from the cache when possible
+    pub async fn get_webpage(
+        &self,
+        level: i8,
+        semester: i8,
+        year: &str,
+    ) -> Result<Html, Box<dyn std::error::Error>> {
+        let html = self.get_raw(level, semester, year).await?;
+
+        // Parse document
+        Ok(Html::parse_document(&html))
+    }
+
+    /// Get the raw HTML of the timetable webpage, from the cache when possible
+    pub async fn get_raw(
+        &self,
+        level: i8,
+        semester: i8,
+        year: &str,
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        Ok(self.get_page(level, semester, year, false).await?.html)
+    }
+
+    /// Get the timetable webpage, from the cache when recent enough unless
+    /// `revalidate` is set, the cached copy is still used when the server fails
+    pub async fn get_page(
+        &self,
+        level: i8,
+        semester: i8,
+        year: &str,
+        revalidate: bool,
+    ) -> Result<Page, Box<dyn std::error::Error>> {
+        if let Some(archive) = &self.offline {
+            return Ok(Page {
+                html: std::fs::read_to_string(archive.join(Self::page_name(level, semester)))?,
+                stale: false,
+            });
+        }
+
+        let url = format!(
+            "{}/U{year}/EDT/visualiserEmploiDuTemps.php?quoi=M{level},{semester}",
+            self.base_url
+        );
+
+        let key = self.cache_key(level, semester, year);
+
+        let cached = self
+            .cache()
+            .filter(|_| !self.refresh)
+            .and_then(|dir| cache::load(dir, &key));
+
+        // Recent enough, no need to ask the server
+        if let Some(entry) = cached
+            .as_ref()
+            .filter(|entry| !revalidate && entry.is_fresh())
+        {
+            return Ok(Page {
+                html: entry.html.clone(),
+                stale: false,
+            });
+        }
+
+        let response = self
+            .send(|| {
+                let mut request = self.client.get(&url);
+
+                // Only download the page if it changed since the cached version
+                if let Some(entry) = &cached {
+                    if let Some(etag) = &entry.etag {
+                        request = request.header(IF_NONE_MATCH, etag);
+                    }
+                    if let Some(last_modified) = &entry.last_modified {
+                        request = request.header(IF_MODIFIED_SINCE, last_modified);
+                    }
+                }
+
+                request
+            })
+            .await
+            .and_then(Response::error_for_status);
+
+        // An outdated page is better than nothing when the server fails
+        let response = match (response, &cached) {
+            (Ok(response), _) => response,
+            (Err(err), Some(entry)) => {
+                eprintln!("{}", Message::StaleCache(&err));
+                return Ok(Page {
+                    html: entry.html.clone(),
+                    stale: true,
+                });
+            }
+            (Err(err), None) => return Err(err.into()),
+        };
+
+        let entry = match cached {
+            Some(mut entry) if response.status() == StatusCode::NOT_MODIFIED => {
+                entry.fetched = SystemTime::now();
+                entry
+            }
+            _ => {
+                let header = |name| {
+                    response
+                        .headers()
+                        .get(name)
+                        .and_then(|value: &HeaderValue| value.to_str().ok())
+                        .map(str::to_owned)
+                };
+                let etag = header(ETAG);
+                let last_modified = header(LAST_MODIFIED);
+
+                let html = response.text().await?;
+
+                check_errors(&html, &url)?;
+
+                cache::Entry {
+                    html,
+                    etag,
+                    last_modified,
+                    fetched: SystemTime::now(),
+                }
+            }
+        };
+        if let Some(dir) = self.cache() {
+            cache::store(dir, &key, &entry);
+        }
+
+        Ok(Page {
+            html: entry.html,
+            stale: false,
+        })
+    }
+
+    /// Send the request, retrying with backoff on transient errors
+    pub async fn send(
+        &self,
+        request: impl Fn() -> RequestBuilder,
+    ) -> Result<Response, reqwest::Error> {
+        let mut delay = BACKOFF;
+        let mut attempt = 0;
+        loop {
+            let result = request().send().await;
+
+            let transient = match &result {
+                Ok(response) => {
+                    response.status().is_server_error()
+                        || response.status() == StatusCode::TOO_MANY_REQUESTS
+                }
+                Err(err) => err.is_timeout() || err.is_connect() || err.is_request(),
+            };
+            if !transient || attempt >= self.retries {
+                return result;
+            }
+
+            attempt += 1;
+            tokio::time::sleep(delay).await;
+            delay *= 2;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{Arc, Mutex},
+        time::{Duration, UNIX_EPOCH},
+    };
+
+    use super::Fetcher;
+    use crate::utils::{
+        cache,
+        file::temp_dir,
+        server::{serve, Response},
+    };
+
+    const YEAR: &str = "2026-2027";
+
+    /// Fetcher of the server, with its own cache
+    fn fetcher(url: &str, retries: u32, name: &str) -> Fetcher {
+        Fetcher::new(
+            "test",
+            Duration::from_secs(5),
+            None,
+            Some(url),
+            retries,
+            false,
+            None,
+        )
+        .unwrap()
+        .with_cache(Some(temp_dir(name)))
+    }
+
+    /// Pretend the page was fetched long ago
+    fn age(fetcher: &Fetcher) {
+        let dir = fetcher.cache().unwrap();
+        let key = fetcher.cache_key(1, 1, YEAR);
+        let mut entry = cache::load(dir, &key).unwrap();
+        entry.fetched = UNIX_EPOCH;
+        cache::store(dir, &key, &entry);
+    }
+
+    #[tokio::test]
+    async fn pages_are_cached() {
+        let requests = Arc::new(Mutex::new(0));
+        let count = Arc::clone(&requests);
+        let url = serve(move |_| {
+            *count.lock().unwrap() += 1;
+            Response::new("200 OK", format!("v{}", count.lock().unwrap()))
+        });
+        let fetcher = fetcher(&url, 0, "cached");
+
+        assert_eq!(fetcher.get_raw(1, 1, YEAR).await.unwrap(), "v1");
+        assert_eq!(fetcher.get_raw(1, 1, YEAR).await.unwrap(), "v1");
+        assert_eq!(*requests.lock().unwrap(), 1);
+
+        // Too old, asked again
+        age(&fetcher);
+        assert_eq!(fetcher.get_raw(1, 1, YEAR).await.unwrap(), "v2");
+        assert_eq!(*requests.lock().unwrap(), 2);
+
+        let _ = std::fs::remove_dir_all(fetcher.cache().unwrap());
+    }
+
+    #[tokio::test]
+    async fn outdated_pages_are_revalidated() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let requests = Arc::clone(&log);
+        let url = serve(move |request| {
+            let validators = (
+                request.headers.get("if-none-match").cloned(),
+                request.headers.get("if-modified-since").cloned(),
+            );
+            let unchanged = validators.0.is_some();
+            requests.lock().unwrap().push(validators);
+
+            if unchanged {
+                Response::new("304 Not Modified", "")
+            } else {
+                Response::new("200 OK", "page")
+                    .header("ETag", "\"1\"")
+                    .header("Last-Modified", "Tue, 01 Sep 2026 08:00:00 GMT")
+            }
+        });
+        let fetcher = fetcher(&url, 0, "revalidated");
+
+        fetcher.get_raw(1, 1, YEAR).await.unwrap();
+        age(&fetcher);
+        assert_eq!(fetcher.get_raw(1, 1, YEAR).await.unwrap(), "page");
+        assert_eq!(
+            log.lock().unwrap()[1],
+            (
+                Some("\"1\"".to_owned()),
+                Some("Tue, 01 Sep 2026 08:00:00 GMT".to_owned())
+            )
+        );
+
+        // Checked again, fresh until the end of the TTL
+        assert_eq!(fetcher.get_raw(1, 1, YEAR).await.unwrap(), "page");
+        assert_eq!(log.lock().unwrap().len(), 2);
+
+        let _ = std::fs::remove_dir_all(fetcher.cache().unwrap());
+    }
+
+    #[tokio::test]
+    async fn outdated_pages_are_used_when_the_server_fails() {
+        let failing = Arc::new(Mutex::new(false));
+        let fails = Arc::clone(&failing);
+        let url = serve(move |_| {
+            if *fails.lock().unwrap() {
+                Response::new("500 Internal Server Error", "")
+            } else {
+                Response::new("200 OK", "page")
+            }
+        });
+        let fetcher = fetcher(&url, 0, "stale");
+
+        fetcher.get_raw(1, 1, YEAR).await.unwrap();
+        age(&fetcher);
+        *failing.lock().unwrap() = true;
+        assert_eq!(fetcher.get_raw(1, 1, YEAR).await.unwrap(), "page");
+
+        // Nothing to fall back on
+        assert!(fetcher.get_raw(2, 1, YEAR).await.is_err());
+
+        let _ = std::fs::remove_dir_all(fetcher.cache().unwrap());
+    }
+
+    #[tokio::test]
+    async fn pages_are_asked_to_the_given_server() {
+        let paths = Arc::new(Mutex::new(Vec::new()));
+        let log = Arc::clone(&paths);
+        let url = serve(move |request| {
+            log.lock().unwrap().push(request.path.clone());
+            Response::new("200 OK", "page")
+        });
+        let fetcher = fetcher(&format!("{url}/ufr/"), 0, "server");
+
+        fetcher.get_raw(2, 1, YEAR).await.unwrap();
+        assert_eq!(
+            *paths.lock().unwrap(),
+            ["/ufr/U2026-2027/EDT/visualiserEmploiDuTemps.php?quoi=M2,1"]
+        );
+
+        let _ = std::fs::remove_dir_all(fetcher.cache().unwrap());
+    }
+
+    #[tokio::test]
+    async fn transient_errors_are_retried() {
+        let requests = Arc::new(Mutex::new(0));
+        let count = Arc::clone(&requests);
+        let url = serve(move |request| {
+            let mut count = count.lock().unwrap();
+            *count += 1;
+            match (request.path.contains("M1"), *count) {
+                (true, 1) => Response::new("503 Service Unavailable", ""),
+                (true, _) => Response::new("200 OK", "page"),
+                (false, _) => Response::new("500 Internal Server Error", ""),
+            }
+        });
+        let fetcher = fetcher(&url, 1, "retried");
+
+        assert_eq!(fetcher.get_raw(1, 1, YEAR).await.unwrap(), "page");
+        assert_eq!(*requests.lock().unwrap(), 2);
+
+        // Given up after the retries
+        assert!(fetcher.get_raw(2, 1, YEAR).await.is_err());
+        assert_eq!(*requests.lock().unwrap(), 4);
+
+        let _ = std::fs::remove_dir_all(fetcher.cache().unwrap());
+    }
+}
@@ -0,0 +1,109 @@
+use std::{
+    io::{self, Write},
+    path::Path,
+};
+
+/// Write the content to the file, or to the standard output with `-`
+pub fn write(filename: &str, content: &str) -> io::Result<()> {
+    if filename == "-" {
+        let mut stdout = io::stdout().lock();
+        stdout.write_all(content.as_bytes())?;
+        return stdout.flush();
+    }
+
+    write_atomic(Path::new(filename), content)
+}
+
+/// Write the content to a temporary file renamed over the destination, so
+/// readers of the file never see it half written
+pub fn write_atomic(path: &Path, content: &str) -> io::Result<()> {
+    let name = path
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "not a file name"))?;
+    let temporary = path.with_file_name(format!(
+        ".{}.{}.tmp",
+        name.to_string_lossy(),
+        std::process::id()
+    ));
+
+    let result = std::fs::File::create(&temporary)
+        .and_then(|mut file| {
+            file.write_all(content.as_bytes())?;
+            file.sync_all()
+        })
+        .and_then(|()| std::fs::rename(&temporary, path));
+
+    // Don't leave the temporary file behind
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temporary);
+    }
+
+    result
+}
+
+/// Empty directory for the files of a test
+#[cfg(test)]
+pub(crate) fn temp_dir(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("cal7tor-{}-{name}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+
+    dir
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::{temp_dir, write, write_atomic};
+    use crate::i18n::Message;
+
+    /// Names of the files of the directory
+    fn files(dir: &Path) -> Vec<String> {
+        let mut files = std::fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect::<Vec<_>>();
+        files.sort();
+        files
+    }
+
+    #[test]
+    fn replaced_at_once() {
+        let dir = temp_dir("atomic");
+        let path = dir.join("calendar.ics");
+
+        write_atomic(&path, "first").unwrap();
+        write(path.to_str().unwrap(), "second").unwrap();
+
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "second");
+        // No temporary file left behind
+        assert_eq!(files(&dir), ["calendar.ics"]);
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn failures() {
+        let dir = temp_dir("failure");
+
+        // A directory can't be replaced by a file
+        let path = dir.join("calendar");
+        std::fs::create_dir(&path).unwrap();
+        let err = write_atomic(&path, "content").unwrap_err();
+        assert_eq!(files(&dir), ["calendar"]);
+        assert!(Message::WriteFailed("calendar", &err)
+            .to_string()
+            .starts_with("Impossible d'écrire calendar : "));
+
+        assert!(write_atomic(&dir.join("missing/calendar.ics"), "content").is_err());
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn standard_output() {
+        write("-", "").unwrap();
+        assert!(!Path::new("-").exists());
+    }
+}
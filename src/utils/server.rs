@@ -0,0 +1,105 @@
+//! Local HTTP server standing in for the real ones in the tests
+
+use std::{
+    collections::HashMap,
+    io::{BufRead, BufReader, Read, Write},
+    net::TcpListener,
+};
+
+/// Request received by the server
+pub struct Request {
+    /// HTTP method, i.e.: `GET`
+    pub method: String,
+    /// Path with the query string
+    pub path: String,
+    /// Headers, with lowercase names
+    pub headers: HashMap<String, String>,
+    /// Body of the request
+    pub body: String,
+}
+
+/// Response sent back by the server
+pub struct Response {
+    /// Status line, i.e.: `200 OK`
+    status: &'static str,
+    /// Headers of the response
+    headers: Vec<(&'static str, String)>,
+    /// Body of the response
+    body: String,
+}
+
+impl Response {
+    /// Response with the status and the body
+    pub fn new(status: &'static str, body: impl Into<String>) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: body.into(),
+        }
+    }
+
+    /// Add a header to the response
+    pub fn header(mut self, name: &'static str, value: impl Into<String>) -> Self {
+        self.headers.push((name, value.into()));
+        self
+    }
+}
+
+/// Answer the requests with the handler, returns the URL of the server
+pub fn serve(mut handler: impl FnMut(&Request) -> Response + Send + 'static) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            let mut parts = line.split_whitespace();
+            let method = parts.next().unwrap().to_owned();
+            let path = parts.next().unwrap().to_owned();
+
+            let mut headers = HashMap::new();
+            loop {
+                line.clear();
+                reader.read_line(&mut line).unwrap();
+                match line.trim_end().split_once(": ") {
+                    Some((key, value)) => {
+                        headers.insert(key.to_lowercase(), value.to_owned());
+                    }
+                    None => break,
+                }
+            }
+            let mut body = vec![
+                0;
+                headers
+                    .get("content-length")
+                    .map_or(0, |length| length.parse().unwrap())
+            ];
+            reader.read_exact(&mut body).unwrap();
+
+            let response = handler(&Request {
+                method,
+                path,
+                headers,
+                body: String::from_utf8(body).unwrap(),
+            });
+
+            write!(stream, "HTTP/1.1 {}\r\n", response.status).unwrap();
+            for (name, value) in &response.headers {
+                write!(stream, "{name}: {value}\r\n").unwrap();
+            }
+            write!(
+                stream,
+                "Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+                response.body.len(),
+                response.body
+            )
+            .unwrap();
+        }
+    });
+
+    url
+}